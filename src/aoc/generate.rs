@@ -0,0 +1,383 @@
+use aoc::grid::{Grid, Pt, FOUR};
+use aoc::rng::Rng;
+use aoc::segments;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Write;
+
+// The size a day's input has when none is given, which is about the size of
+// the real puzzle inputs.
+pub fn default_size(day: usize) -> usize {
+	match day {
+		1 | 2 | 3 | 7 => 1000,
+		4 | 9 => 100,
+		5 | 6 => 300,
+		_ => 200,
+	}
+}
+
+// Generates an input for a day in the same format as the puzzle input. The
+// same seed and size always generate the same input. What size means depends
+// on the day, but bigger is always a bigger input.
+pub fn generate(day: usize, seed: u64, size: usize) -> Result<String, Box<dyn Error>> {
+	let mut rng = Rng::new(seed);
+	let size = size.max(1);
+	Ok(match day {
+		1 => depths(&mut rng, size),
+		2 => commands(&mut rng, size),
+		3 => report(&mut rng, size),
+		4 => bingo(&mut rng, size),
+		5 => vents(&mut rng, size),
+		6 => fish(&mut rng, size),
+		7 => crabs(&mut rng, size),
+		8 => displays(&mut rng, size),
+		9 => heightmap(&mut rng, size),
+		_ => return Err(format!("no generator for day {}", day).into()),
+	})
+}
+
+// size depths that mostly go deeper, like a sonar sweep of a sloping floor.
+fn depths(rng: &mut Rng, size: usize) -> String {
+	let mut out = String::new();
+	let mut depth = rng.between(100, 200);
+	for _ in 0..size {
+		writeln!(out, "{}", depth).unwrap();
+		depth = (depth + rng.between(-8, 12)).max(1);
+	}
+	out
+}
+
+// size commands that never take the submarine above the surface. day02
+// counts in i32, so a command is only used if the position times the depth
+// still fits afterwards for both parts, otherwise the submarine heads up, or
+// bobs down 1 when it's already at the surface.
+fn commands(rng: &mut Rng, size: usize) -> String {
+	const LIMIT: i64 = i32::MAX as i64;
+	let mut out = String::new();
+	// the depth is the aim in part 2
+	let (mut x, mut depth, mut deeper) = (0_i64, 0_i64, 0_i64);
+	for _ in 0..size {
+		let n = rng.between(1, 9) as i64;
+		let (dir, n) = match rng.below(3) {
+			0 if (x + n) * (deeper + depth * n).max(depth).max(1) <= LIMIT => ("forward", n),
+			1 if x * (depth + n) <= LIMIT => ("down", n),
+			_ if depth > 0 => ("up", n.min(depth)),
+			_ => ("down", 1),
+		};
+		match dir {
+			"forward" => {
+				x += n;
+				deeper += depth * n;
+			}
+			"down" => depth += n,
+			_ => depth -= n,
+		}
+		writeln!(out, "{} {}", dir, n).unwrap();
+	}
+	out
+}
+
+// size different 12 bit numbers where each bit has its own leaning towards 1,
+// so the most and least common bits differ from column to column. Like the
+// puzzle's, no number is repeated and the report is one day03 can rate, so
+// there are between 2 and 4096 numbers.
+fn report(rng: &mut Rng, size: usize) -> String {
+	const BITS: usize = 12;
+	let size = size.clamp(2, 1 << BITS);
+	let odds = (0..BITS)
+		.map(|_| rng.between(20, 80) as usize)
+		.collect::<Vec<_>>();
+	let mut seen = vec![false; 1 << BITS];
+	let mut vals = Vec::with_capacity(size);
+	for _ in 0..size * 20 {
+		if vals.len() == size {
+			break;
+		}
+		let val = odds
+			.iter()
+			.fold(0, |val, &odds| val << 1 | (rng.below(100) < odds) as usize);
+		if !seen[val] {
+			seen[val] = true;
+			vals.push(val);
+		}
+	}
+	// the leanings make some numbers hard to come by as the report fills up,
+	// so the rest are any of the numbers that are left
+	let mut rest = (0..1 << BITS).filter(|&v| !seen[v]).collect::<Vec<_>>();
+	rng.shuffle(&mut rest);
+	vals.extend(rest.into_iter().take(size - vals.len()));
+	make_rateable(&mut vals, BITS);
+
+	let mut out = String::new();
+	for val in vals {
+		writeln!(out, "{:0width$b}", val, width = BITS).unwrap();
+	}
+	out
+}
+
+// Changes a report so day03 can rate it, which it can for the puzzle's
+// reports but not for every set of numbers. It rates oxygen from the numbers
+// with the top bit set and CO2 from the rest, so the top bit must be set in
+// at least as many numbers as not, and in not all of them. While rating CO2
+// it keeps the numbers with the less common bit, so the numbers left can
+// never all have the same bit. Each change flips a single bit of a number,
+// to a number that isn't already in the report.
+fn make_rateable(vals: &mut [usize], bits: usize) {
+	let top = 1 << (bits - 1);
+	let ones = vals.iter().filter(|&&v| v & top != 0).count();
+	if ones * 2 < vals.len() {
+		for v in vals.iter_mut() {
+			*v ^= top;
+		}
+	}
+	if vals.iter().all(|&v| v & top != 0) {
+		vals[0] ^= top;
+	}
+
+	'rate: loop {
+		let mut left = (0..vals.len())
+			.filter(|&i| vals[i] & top == 0)
+			.collect::<Vec<_>>();
+		let mut m = top >> 1;
+		while left.len() > 1 {
+			let (set, unset): (Vec<_>, Vec<_>) = left.iter().partition(|&&i| vals[i] & m != 0);
+			if set.is_empty() || unset.is_empty() {
+				// another number in the same place as this one, but with the
+				// other bit, can't be in the report or it would be left too
+				vals[left[0]] ^= m;
+				continue 'rate;
+			}
+			left = if set.len() < unset.len() { set } else { unset };
+			m >>= 1;
+		}
+		return;
+	}
+}
+
+// Draws every number from 0 to 99 in a random order, followed by size cards
+// of 25 different numbers each.
+fn bingo(rng: &mut Rng, size: usize) -> String {
+	let mut draws = (0..100).collect::<Vec<usize>>();
+	rng.shuffle(&mut draws);
+	let mut out = draws
+		.iter()
+		.map(|n| n.to_string())
+		.collect::<Vec<_>>()
+		.join(",");
+	out.push('\n');
+
+	let mut numbers = (0..100).collect::<Vec<usize>>();
+	for _ in 0..size {
+		rng.shuffle(&mut numbers);
+		out.push('\n');
+		for row in numbers[..25].chunks(5) {
+			let row = row.iter().map(|n| format!("{:2}", n)).collect::<Vec<_>>();
+			writeln!(out, "{}", row.join(" ")).unwrap();
+		}
+	}
+	out
+}
+
+// size lines of vents that are horizontal, vertical or at 45 degrees, which
+// are the only kinds the puzzle has.
+fn vents(rng: &mut Rng, size: usize) -> String {
+	let mut out = String::new();
+	for _ in 0..size {
+		// starting off the edges leaves room to go at least 1 in any direction
+		let (x1, y1) = (rng.between(1, 988), rng.between(1, 988));
+		let (dx, dy) = match rng.below(3) {
+			0 => (1, 0),
+			1 => (0, 1),
+			_ => (1, if rng.below(2) == 0 { 1 } else { -1 }),
+		};
+		// keep the far end on the floor, which is 0..990 in each direction
+		let room = |v: isize, d: isize| match d {
+			1 => 989 - v,
+			-1 => v,
+			_ => isize::MAX,
+		};
+		let len = rng.between(1, 300).min(room(x1, dx)).min(room(y1, dy));
+		let (x2, y2) = (x1 + dx * len, y1 + dy * len);
+		// either end can come first
+		if rng.below(2) == 0 {
+			writeln!(out, "{},{} -> {},{}", x1, y1, x2, y2).unwrap();
+		} else {
+			writeln!(out, "{},{} -> {},{}", x2, y2, x1, y1).unwrap();
+		}
+	}
+	out
+}
+
+fn list(values: impl Iterator<Item = isize>) -> String {
+	let mut out = values.map(|v| v.to_string()).collect::<Vec<_>>().join(",");
+	out.push('\n');
+	out
+}
+
+// size fish with timers from 1 to 5, like the puzzle's.
+fn fish(rng: &mut Rng, size: usize) -> String {
+	list((0..size).map(|_| rng.between(1, 5)))
+}
+
+// size crabs bunched up towards the low positions, with a few stragglers
+// far out.
+fn crabs(rng: &mut Rng, size: usize) -> String {
+	list((0..size).map(|_| {
+		let a = rng.between(0, 1900);
+		let b = rng.between(0, 1900);
+		a.min(b)
+	}))
+}
+
+// size scrambled displays of the digits.
+fn displays(rng: &mut Rng, size: usize) -> String {
+	let digits = segments::parse_glyphs(segments::DIGITS).unwrap();
+	let mut out = String::new();
+	for _ in 0..size {
+		writeln!(out, "{}", segments::generate(&digits, 7, rng).line).unwrap();
+	}
+	out
+}
+
+// A size by size heightmap of basins walled off by 9s. Basins grow out from
+// randomly placed low points at the same pace and the cells where two of them
+// meet become walls, with heights rising away from each low point.
+fn heightmap(rng: &mut Rng, size: usize) -> String {
+	const UNSEEN: usize = usize::MAX;
+	let mut basins = Grid::filled(size, size, UNSEEN);
+	let mut heights = Grid::filled(size, size, 9u8);
+	let mut queue = VecDeque::new();
+	for basin in 0..(size * size / 40).max(1) {
+		let pt: Pt = (rng.below(size), rng.below(size));
+		if basins[pt] == UNSEEN {
+			basins[pt] = basin;
+			heights[pt] = rng.below(2) as u8;
+			queue.push_back(pt);
+		}
+	}
+
+	while let Some(pt) = queue.pop_front() {
+		if heights[pt] == 9 {
+			continue;
+		}
+		for n in basins.neighbors(&pt, FOUR).collect::<Vec<_>>() {
+			if basins[n] == UNSEEN {
+				basins[n] = basins[pt];
+				heights[n] = (heights[pt] + rng.below(2) as u8 + 1).min(8);
+				queue.push_back(n);
+			} else if basins[n] != basins[pt] && heights[n] != 9 {
+				heights[n] = 9;
+			}
+		}
+	}
+
+	let mut out = String::new();
+	for row in heights.rows() {
+		for h in row {
+			out.push((b'0' + h) as char);
+		}
+		out.push('\n');
+	}
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::lint;
+
+	// the oxygen and CO2 ratings the way day03 finds them, or None where it
+	// would run out of numbers
+	fn ratings(vals: &[usize], bits: usize) -> Option<(usize, usize)> {
+		let top = 1 << (bits - 1);
+		let (mut oxygen, mut co2): (Vec<_>, Vec<_>) = vals.iter().partition(|&&v| v & top != 0);
+		let mut m = top >> 1;
+		while oxygen.len() > 1 {
+			let (set, unset): (Vec<_>, Vec<_>) = oxygen.iter().partition(|&&v| v & m != 0);
+			oxygen = if set.len() >= unset.len() { set } else { unset };
+			m >>= 1;
+		}
+		let mut m = top >> 1;
+		while co2.len() > 1 {
+			let (set, unset): (Vec<_>, Vec<_>) = co2.iter().partition(|&&v| v & m != 0);
+			co2 = if set.len() < unset.len() { set } else { unset };
+			m >>= 1;
+		}
+		Some((*oxygen.first()?, *co2.first()?))
+	}
+
+	#[test]
+	fn generated_inputs_lint_clean() {
+		for day in 1..=9 {
+			for seed in 0..4 {
+				for size in [1, 2, 10, default_size(day)] {
+					let input = generate(day, seed, size).unwrap();
+					let problems = lint::lint(day, &input);
+					assert!(
+						problems.is_empty(),
+						"day {} seed {} size {}: {}",
+						day,
+						seed,
+						size,
+						problems[0]
+					);
+				}
+			}
+		}
+		assert!(generate(10, 0, 10).is_err());
+	}
+
+	#[test]
+	fn commands_fit_in_i32() {
+		for seed in 0..20 {
+			let input = commands(&mut Rng::new(seed), 5000);
+			let (mut x, mut depth, mut aim) = (0_i64, 0_i64, 0_i64);
+			for line in input.lines() {
+				let (dir, n) = line.split_once(' ').unwrap();
+				let n: i64 = n.parse().unwrap();
+				match dir {
+					"forward" => {
+						x += n;
+						depth += aim * n;
+					}
+					"down" => aim += n,
+					_ => aim -= n,
+				}
+				assert!(aim >= 0, "seed {}: above the surface", seed);
+				assert!(
+					x * aim <= i32::MAX as i64,
+					"seed {}: part 1 overflows",
+					seed
+				);
+				assert!(
+					x * depth <= i32::MAX as i64,
+					"seed {}: part 2 overflows",
+					seed
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn rateable_reports() {
+		// the numbers without the top bit share the next bit, so rating CO2
+		// keeps neither half
+		let mut vals = vec![0b1000, 0b1001, 0b1010, 0b0000, 0b0001];
+		assert_eq!(ratings(&vals, 4), None);
+		make_rateable(&mut vals, 4);
+		assert!(ratings(&vals, 4).is_some());
+
+		let mut rng = Rng::new(3);
+		for size in [2, 3, 7, 50, 1000] {
+			let mut vals = (0..1 << 10).collect::<Vec<usize>>();
+			rng.shuffle(&mut vals);
+			vals.truncate(size);
+			make_rateable(&mut vals, 10);
+			assert!(ratings(&vals, 10).is_some(), "size {}", size);
+			let mut sorted = vals.clone();
+			sorted.sort_unstable();
+			sorted.dedup();
+			assert_eq!(sorted.len(), size, "size {}: repeated numbers", size);
+		}
+	}
+}
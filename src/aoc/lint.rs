@@ -0,0 +1,531 @@
+use std::collections::HashSet;
+use std::fmt;
+
+// Something wrong with an input, along with how to fix it if there's an
+// obvious way.
+#[derive(Debug)]
+pub struct Problem {
+	pub line: usize,
+	pub column: Option<usize>,
+	pub message: String,
+	pub fix: Option<String>,
+}
+
+impl fmt::Display for Problem {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.column {
+			Some(c) => write!(f, "{}:{}: {}", self.line, c, self.message)?,
+			None => write!(f, "{}: {}", self.line, self.message)?,
+		}
+		if let Some(fix) = &self.fix {
+			write!(f, " ({})", fix)?;
+		}
+		Ok(())
+	}
+}
+
+// The problems found so far, which keeps going after each one so that every
+// problem in an input is reported at once.
+#[derive(Default)]
+struct Lint {
+	problems: Vec<Problem>,
+}
+
+impl Lint {
+	fn error<S: Into<String>>(&mut self, line: usize, column: Option<usize>, message: S) {
+		self.problems.push(Problem {
+			line,
+			column,
+			message: message.into(),
+			fix: None,
+		});
+	}
+
+	fn fixable<S: Into<String>, F: Into<String>>(
+		&mut self,
+		line: usize,
+		column: Option<usize>,
+		message: S,
+		fix: F,
+	) {
+		self.problems.push(Problem {
+			line,
+			column,
+			message: message.into(),
+			fix: Some(fix.into()),
+		});
+	}
+}
+
+// A line of the input with its number, counting from 1.
+type Line<'a> = (usize, &'a str);
+
+// Splits s on sep, returning each field with the column it starts at.
+fn fields(s: &str, sep: char) -> Vec<(usize, &str)> {
+	let mut fields = Vec::new();
+	let mut start = 0;
+	for (i, c) in s.char_indices() {
+		if c == sep {
+			fields.push((start + 1, &s[start..i]));
+			start = i + c.len_utf8();
+		}
+	}
+	fields.push((start + 1, &s[start..]));
+	fields
+}
+
+// Splits s on whitespace, returning each word with the column it starts at.
+fn words(s: &str) -> Vec<(usize, &str)> {
+	fields(s, ' ')
+		.into_iter()
+		.filter(|(_, w)| !w.is_empty())
+		.collect()
+}
+
+// Checks the things that trip up every day: CRLF line endings, trailing
+// whitespace and blank lines at the end. Returns the lines with those
+// stripped so the same problem isn't reported again by the day's checks.
+fn check_lines<'a>(lint: &mut Lint, text: &'a str) -> Vec<Line<'a>> {
+	if text.is_empty() {
+		lint.error(1, None, "the input is empty");
+		return Vec::new();
+	}
+
+	let mut lines = Vec::new();
+	let mut crlf = Vec::new();
+	for (i, line) in text.split('\n').enumerate() {
+		let n = i + 1;
+		let line = match line.strip_suffix('\r') {
+			Some(l) => {
+				crlf.push(n);
+				l
+			}
+			None => line,
+		};
+		let trimmed = line.trim_end();
+		if trimmed.len() != line.len() {
+			lint.fixable(
+				n,
+				Some(trimmed.len() + 1),
+				"trailing whitespace",
+				"strip the whitespace at the end of the line",
+			);
+		}
+		lines.push((n, trimmed));
+	}
+	if let Some(&first) = crlf.first() {
+		lint.fixable(
+			first,
+			None,
+			format!("{} lines end with CRLF", crlf.len()),
+			"convert the line endings to LF, e.g. with dos2unix",
+		);
+	}
+
+	// a final newline leaves an empty last line, which is expected
+	if text.ends_with('\n') {
+		lines.pop();
+	}
+	let blank = lines.iter().rev().take_while(|(_, l)| l.is_empty()).count();
+	if blank > 0 && blank < lines.len() {
+		let (first, _) = lines[lines.len() - blank];
+		lint.fixable(
+			first,
+			None,
+			format!("{} blank lines at the end of the input", blank),
+			"remove the blank lines after the last line",
+		);
+		lines.truncate(lines.len() - blank);
+	}
+	lines
+}
+
+fn check_number<T: std::str::FromStr>(
+	lint: &mut Lint,
+	line: usize,
+	column: usize,
+	s: &str,
+) -> Option<T> {
+	match s.parse::<T>() {
+		Ok(v) => Some(v),
+		Err(_) if s.is_empty() => {
+			lint.error(line, Some(column), "missing number");
+			None
+		}
+		Err(_) => {
+			lint.error(line, Some(column), format!("invalid number: {}", s));
+			None
+		}
+	}
+}
+
+// Every line must be something other than blank.
+fn check_no_blanks(lint: &mut Lint, lines: &[Line]) {
+	for &(n, line) in lines {
+		if line.is_empty() {
+			lint.fixable(n, None, "blank line", "remove the line");
+		}
+	}
+}
+
+fn check_day01(lint: &mut Lint, lines: &[Line]) {
+	check_no_blanks(lint, lines);
+	for &(n, line) in lines.iter().filter(|(_, l)| !l.is_empty()) {
+		check_number::<i32>(lint, n, 1, line);
+	}
+}
+
+fn check_day02(lint: &mut Lint, lines: &[Line]) {
+	check_no_blanks(lint, lines);
+	for &(n, line) in lines.iter().filter(|(_, l)| !l.is_empty()) {
+		match line.split_once(' ') {
+			Some(("forward" | "down" | "up", v)) => {
+				check_number::<i32>(lint, n, line.len() - v.len() + 1, v);
+			}
+			Some((cmd, _)) => lint.error(
+				n,
+				Some(1),
+				format!("unknown command {}, expected forward, down or up", cmd),
+			),
+			None => lint.error(
+				n,
+				Some(1),
+				"expected a command and a distance, like forward 5",
+			),
+		}
+	}
+}
+
+fn check_day03(lint: &mut Lint, lines: &[Line]) {
+	check_no_blanks(lint, lines);
+	let width = lines.iter().map(|(_, l)| l.len()).find(|&w| w > 0);
+	for &(n, line) in lines.iter().filter(|(_, l)| !l.is_empty()) {
+		if let Some((i, c)) = line.char_indices().find(|&(_, c)| c != '0' && c != '1') {
+			lint.error(n, Some(i + 1), format!("invalid bit: {}", c));
+		}
+		if Some(line.len()) != width {
+			lint.error(
+				n,
+				None,
+				format!(
+					"expected {} bits like the first line, found {}",
+					width.unwrap(),
+					line.len()
+				),
+			);
+		}
+	}
+	if width.is_some_and(|w| w > 16) {
+		lint.error(1, None, "numbers with more than 16 bits are too wide");
+	}
+}
+
+// Checks the rows of a single card, which are 5 numbers lined up in 3
+// character columns.
+fn check_card(lint: &mut Lint, rows: &[Line]) {
+	let mut seen = HashSet::new();
+	for &(n, row) in rows {
+		let nums = words(row);
+		if nums.len() != 5 {
+			lint.error(n, None, format!("expected 5 numbers, found {}", nums.len()));
+		}
+		for (c, s) in nums {
+			let v = match check_number::<u8>(lint, n, c, s) {
+				Some(v) => v,
+				None => continue,
+			};
+			// the numbers are read from the first two characters of each
+			// column
+			if (c + s.len() - 1) % 3 != 2 {
+				lint.fixable(
+					n,
+					Some(c),
+					format!("{} is not lined up with the other numbers", v),
+					"right align each number in a column 3 characters wide",
+				);
+			}
+			if !seen.insert(v) {
+				lint.error(
+					n,
+					Some(c),
+					format!("{} appears more than once on the card", v),
+				);
+			}
+		}
+	}
+}
+
+// Checks the draws on the first line followed by cards of 5 rows, each after
+// a blank line.
+fn check_day04(lint: &mut Lint, lines: &[Line]) {
+	let (n, draws) = match lines.first() {
+		Some(&l) => l,
+		None => return,
+	};
+	let mut drawn = HashSet::new();
+	for (c, v) in fields(draws, ',') {
+		if let Some(v) = check_number::<u8>(lint, n, c, v.trim()) {
+			if !drawn.insert(v) {
+				lint.error(n, Some(c), format!("{} is drawn more than once", v));
+			}
+		}
+	}
+
+	// the cards are the runs of rows between blank lines
+	let mut cards = Vec::new();
+	let mut start = None;
+	for (i, &(n, line)) in lines.iter().enumerate().skip(1) {
+		if line.is_empty() {
+			match start.take() {
+				Some(s) => cards.push(&lines[s..i]),
+				// the line after the draws is the only blank line that isn't
+				// right after a card
+				None if i > 1 => lint.fixable(n, None, "extra blank line", "remove the line"),
+				None => {}
+			}
+		} else if start.is_none() {
+			if i == 1 {
+				lint.fixable(
+					n,
+					Some(1),
+					"expected a blank line before the card",
+					"add a blank line after the draws",
+				);
+			}
+			start = Some(i);
+		}
+	}
+	if let Some(s) = start {
+		cards.push(&lines[s..]);
+	}
+
+	for card in cards {
+		if card.len() > 5 && card.len() % 5 == 0 {
+			// cards that run together without a blank line between them
+			for rows in card.chunks(5).skip(1) {
+				lint.fixable(
+					rows[0].0,
+					Some(1),
+					"expected a blank line before the card",
+					"add a blank line between cards",
+				);
+			}
+			for rows in card.chunks(5) {
+				check_card(lint, rows);
+			}
+		} else {
+			if card.len() != 5 {
+				lint.error(
+					card[0].0,
+					None,
+					format!("card has {} rows, expected 5", card.len()),
+				);
+			}
+			check_card(lint, card);
+		}
+	}
+}
+
+fn check_day05(lint: &mut Lint, lines: &[Line]) {
+	check_no_blanks(lint, lines);
+	for &(n, line) in lines.iter().filter(|(_, l)| !l.is_empty()) {
+		let ends = match line.split_once(" -> ") {
+			Some((a, b)) => [(1, a), (line.len() - b.len() + 1, b)],
+			None => {
+				lint.error(n, None, "expected a line like 0,9 -> 5,9");
+				continue;
+			}
+		};
+		for (c, pt) in ends {
+			match pt.split_once(',') {
+				Some((x, y)) => {
+					check_number::<isize>(lint, n, c, x);
+					check_number::<isize>(lint, n, c + x.len() + 1, y);
+				}
+				None => lint.error(
+					n,
+					Some(c),
+					format!("expected a point like 0,9, found {}", pt),
+				),
+			}
+		}
+	}
+}
+
+// Checks a single line of numbers separated by commas, like days 6 and 7.
+fn check_list<T>(lint: &mut Lint, lines: &[Line], max: Option<T>)
+where
+	T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+	let (n, line) = match lines.first() {
+		Some(&l) => l,
+		None => return,
+	};
+	for (c, v) in fields(line, ',') {
+		if v.trim() != v {
+			lint.fixable(
+				n,
+				Some(c),
+				"space in the list",
+				"remove the spaces between numbers",
+			);
+		}
+		match (check_number::<T>(lint, n, c, v.trim()), &max) {
+			(Some(v), Some(max)) if v > *max => {
+				lint.error(n, Some(c), format!("{} is more than {}", v, max))
+			}
+			_ => {}
+		}
+	}
+	for &(n, _) in &lines[1..] {
+		lint.error(n, None, "expected every number on one line");
+	}
+}
+
+fn check_day08(lint: &mut Lint, lines: &[Line]) {
+	check_no_blanks(lint, lines);
+	for &(n, line) in lines.iter().filter(|(_, l)| !l.is_empty()) {
+		let (patterns, output) = match line.split_once(" | ") {
+			Some(p) => p,
+			None => {
+				lint.error(n, None, "expected patterns and output separated by |");
+				continue;
+			}
+		};
+		let offset = patterns.len() + 3;
+		let patterns = words(patterns);
+		let output = words(output)
+			.into_iter()
+			.map(|(c, p)| (c + offset, p))
+			.collect::<Vec<_>>();
+		if patterns.len() != 10 {
+			lint.error(
+				n,
+				None,
+				format!("expected 10 patterns, found {}", patterns.len()),
+			);
+		}
+		if output.len() != 4 {
+			lint.error(
+				n,
+				None,
+				format!("expected 4 output patterns, found {}", output.len()),
+			);
+		}
+
+		let mut seen = HashSet::new();
+		let mut lengths = [0; 8];
+		for &(c, p) in patterns.iter().chain(&output) {
+			let mut wires = HashSet::new();
+			let mut valid = true;
+			for (i, w) in p.char_indices() {
+				if !('a'..='g').contains(&w) {
+					lint.error(n, Some(c + i), format!("invalid wire: {}", w));
+					valid = false;
+				} else if !wires.insert(w) {
+					lint.error(n, Some(c + i), format!("wire {} is repeated in {}", w, p));
+					valid = false;
+				}
+			}
+			if valid && !(2..=7).contains(&wires.len()) {
+				lint.error(
+					n,
+					Some(c),
+					format!("no digit has {} segments: {}", wires.len(), p),
+				);
+			}
+		}
+		for &(c, p) in &patterns {
+			let mut wires = p.chars().collect::<Vec<_>>();
+			wires.sort_unstable();
+			wires.dedup();
+			if !seen.insert(wires.clone()) {
+				lint.error(n, Some(c), format!("{} is the same as another pattern", p));
+			}
+			if wires.len() < lengths.len() {
+				lengths[wires.len()] += 1;
+			}
+		}
+		// every digit shows up once, which has this many of each length
+		if patterns.len() == 10 && lengths != [0, 0, 1, 1, 1, 3, 3, 1] {
+			lint.error(n, None, "the patterns can't be the ten digits");
+		}
+	}
+}
+
+// Checks a heightmap, which is either a digit for each point or numbers
+// separated by commas or whitespace, with the same width for every row.
+// The same rules as day09's Map::parse_heights: if any row has a separator,
+// every row is heights separated by commas or whitespace, otherwise every
+// row is digits.
+fn check_day09(lint: &mut Lint, lines: &[Line]) {
+	check_no_blanks(lint, lines);
+	let is_separated = |l: &str| l.trim_start().contains([',', ' ', '\t']);
+	let separated = lines.iter().any(|&(_, l)| is_separated(l));
+	let mut width = None;
+	for &(n, line) in lines.iter().filter(|(_, l)| !l.is_empty()) {
+		let count = if separated {
+			let heights = line
+				.split(|c: char| c == ',' || c.is_whitespace())
+				.scan(1, |c, h| {
+					let col = *c;
+					*c += h.chars().count() + 1;
+					Some((col, h))
+				})
+				.filter(|(_, h)| !h.is_empty())
+				.collect::<Vec<_>>();
+			for &(c, h) in &heights {
+				check_number::<u32>(lint, n, c, h);
+			}
+			heights.len()
+		} else {
+			let row = line.trim_start();
+			let start = line.chars().count() - row.chars().count();
+			for (i, c) in row.chars().enumerate() {
+				if !c.is_ascii_digit() {
+					lint.error(n, Some(start + i + 1), format!("invalid height: {}", c));
+				}
+			}
+			row.chars().count()
+		};
+		match width {
+			None => width = Some(count),
+			Some(w) if w != count && !is_separated(line) && separated => lint.error(
+				n,
+				None,
+				format!(
+					"expected {} heights like the first row, found a row without separators",
+					w
+				),
+			),
+			Some(w) if w != count => lint.error(
+				n,
+				None,
+				format!("expected {} heights like the first row, found {}", w, count),
+			),
+			_ => {}
+		}
+	}
+}
+
+// Checks an input for the given day, returning every problem found.
+pub fn lint(day: usize, text: &str) -> Vec<Problem> {
+	let mut lint = Lint::default();
+	let lines = check_lines(&mut lint, text);
+	match day {
+		1 => check_day01(&mut lint, &lines),
+		2 => check_day02(&mut lint, &lines),
+		3 => check_day03(&mut lint, &lines),
+		4 => check_day04(&mut lint, &lines),
+		5 => check_day05(&mut lint, &lines),
+		// a new fish starts at 8, so no fish should be any older
+		6 => check_list::<usize>(&mut lint, &lines, Some(8)),
+		// crabs can be at negative positions
+		7 => check_list::<isize>(&mut lint, &lines, None),
+		8 => check_day08(&mut lint, &lines),
+		9 => check_day09(&mut lint, &lines),
+		_ => lint.error(1, None, format!("there is no check for day {}", day)),
+	}
+	lint.problems.sort_by_key(|p| (p.line, p.column));
+	lint.problems
+}
@@ -0,0 +1,302 @@
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+mod generate;
+mod lint;
+mod pool;
+mod watch;
+
+// the days that have been solved so far.
+const DAYS: usize = 9;
+
+// the executable for a day, which cargo builds next to this one.
+fn exe_of(day: usize) -> Result<PathBuf, Box<dyn Error>> {
+	Ok(std::env::current_exe()?.with_file_name(format!(
+		"day{:02}{}",
+		day,
+		std::env::consts::EXE_SUFFIX
+	)))
+}
+
+// The executables for the days. These aren't built here, so they need to
+// have been built along with this runner, like with cargo build --bins.
+fn exes_of(days: &[usize]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+	let exes = days
+		.iter()
+		.map(|&day| exe_of(day))
+		.collect::<Result<Vec<_>, _>>()?;
+	let missing = days
+		.iter()
+		.zip(exes.iter())
+		.filter(|(_, exe)| !exe.is_file())
+		.map(|(day, _)| format!("day{:02}", day))
+		.collect::<Vec<_>>();
+	if !missing.is_empty() {
+		return Err(format!(
+			"{} not built next to this runner, build every day with cargo build --bins",
+			missing.join(", ")
+		)
+		.into());
+	}
+	Ok(exes)
+}
+
+// Parses the days to run, which are either numbers like 7 or ranges like
+// 3-5. No days at all means every day.
+fn parse_days<'a, I>(args: I) -> Result<Vec<usize>, Box<dyn Error>>
+where
+	I: Iterator<Item = &'a str>,
+{
+	let mut days = Vec::new();
+	for arg in args {
+		let day = |s: &str| match s.trim_start_matches("day").parse::<usize>() {
+			Ok(d) if (1..=DAYS).contains(&d) => Ok(d),
+			_ => Err(format!("invalid day: {}", s)),
+		};
+		match arg.split_once('-') {
+			Some((a, b)) => {
+				let (a, b) = (day(a)?, day(b)?);
+				if a > b {
+					return Err(format!("invalid range: {} runs backwards", arg).into());
+				}
+				days.extend(a..=b);
+			}
+			None => days.push(day(arg)?),
+		}
+	}
+	if days.is_empty() {
+		days.extend(1..=DAYS);
+	}
+	Ok(days)
+}
+
+#[derive(Debug)]
+struct Task {
+	day: usize,
+	exe: PathBuf,
+	input: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+struct Outcome {
+	elapsed: Duration,
+	// what the day printed, or why it failed
+	output: Result<String, String>,
+}
+
+fn run_day(task: Task) -> Outcome {
+	let mut cmd = Command::new(&task.exe);
+	if let Some(input) = &task.input {
+		cmd.arg(input);
+	}
+
+	let start = Instant::now();
+	let output = cmd.output();
+	let elapsed = start.elapsed();
+
+	let output = match output {
+		Err(e) => Err(format!("could not run {}: {}", task.exe.display(), e)),
+		Ok(o) if !o.status.success() => Err(format!(
+			"day {} {}: {}",
+			task.day,
+			o.status,
+			String::from_utf8_lossy(&o.stderr).trim()
+		)),
+		Ok(o) => Ok(String::from_utf8_lossy(&o.stdout).into_owned()),
+	};
+	Outcome { elapsed, output }
+}
+
+fn run(matches: &clap::ArgMatches) -> Result<(), Box<dyn Error>> {
+	let days = parse_days(matches.values_of("days").into_iter().flatten())?;
+	let jobs = match matches.value_of("jobs") {
+		Some(n) => n.parse()?,
+		None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+	};
+
+	let tasks = days
+		.iter()
+		.zip(exes_of(&days)?)
+		.map(|(&day, exe)| Task {
+			day,
+			exe,
+			input: matches
+				.value_of("data")
+				.map(|dir| PathBuf::from(dir).join(format!("day{:02}/input.txt", day))),
+		})
+		.collect::<Vec<_>>();
+
+	let start = Instant::now();
+	let outcomes = pool::run(jobs, tasks, run_day, |i, outcome| {
+		println!("day{:02} ({:.1?})", days[i], outcome.elapsed);
+		match &outcome.output {
+			Ok(out) => {
+				for line in out.lines() {
+					println!("  {}", line);
+				}
+			}
+			Err(err) => println!("  error: {}", err),
+		}
+	});
+
+	let failed = outcomes.iter().filter(|o| o.output.is_err()).count();
+	println!(
+		"{} days in {:.1?} running {} at once ({:.1?} added up)",
+		days.len(),
+		start.elapsed(),
+		jobs.min(days.len()).max(1),
+		outcomes.iter().map(|o| o.elapsed).sum::<Duration>()
+	);
+	if failed > 0 {
+		return Err(format!("{} of {} days failed", failed, days.len()).into());
+	}
+	Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+	let matches = clap::App::new("aoc")
+		.about("runs the solutions for each day")
+		.setting(clap::AppSettings::SubcommandRequiredElseHelp)
+		.subcommand(
+			clap::SubCommand::with_name("run")
+				.about("run days at the same time and show their answers in order")
+				.arg(
+					clap::Arg::with_name("days")
+						.multiple(true)
+						.help("the days to run, like 3 or 3-5, or every day if none are given"),
+				)
+				.arg(
+					clap::Arg::with_name("jobs")
+						.long("jobs")
+						.short("j")
+						.takes_value(true)
+						.help("the number of days to run at once, which defaults to the number of cpus"),
+				)
+				.arg(
+					clap::Arg::with_name("data")
+						.long("data")
+						.takes_value(true)
+						.help("the directory with an input.txt for each day, like data/day01/input.txt"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("watch")
+				.about("rebuild and check a day against its recorded answers whenever it changes")
+				.arg(
+					clap::Arg::with_name("day")
+						.required(true)
+						.help("the day to watch"),
+				)
+				.arg(
+					clap::Arg::with_name("interval")
+						.long("interval")
+						.takes_value(true)
+						.default_value("500")
+						.help("how often to look for changes, in milliseconds"),
+				)
+				.arg(
+					clap::Arg::with_name("record")
+						.long("record")
+						.help("record the answers for inputs that don't have any yet"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("generate")
+				.about("generate a random input for a day in the same format as the puzzle's")
+				.arg(
+					clap::Arg::with_name("day")
+						.required(true)
+						.help("the day to generate an input for"),
+				)
+				.arg(
+					clap::Arg::with_name("seed")
+						.long("seed")
+						.takes_value(true)
+						.default_value("2021")
+						.help("the seed for the generator, which always generates the same input"),
+				)
+				.arg(
+					clap::Arg::with_name("size")
+						.long("size")
+						.takes_value(true)
+						.help("how big the input is, like the number of lines, which defaults to about the size of the puzzle's"),
+				)
+				.arg(
+					clap::Arg::with_name("output")
+						.long("output")
+						.short("o")
+						.takes_value(true)
+						.help("the file to write the input to instead of stdout"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("lint")
+				.about("check that an input is well formed for a day")
+				.arg(
+					clap::Arg::with_name("day")
+						.required(true)
+						.help("the day the input is for"),
+				)
+				.arg(
+					clap::Arg::with_name("file")
+						.required(true)
+						.help("the input file"),
+				),
+		)
+		.get_matches();
+
+	match matches.subcommand() {
+		("generate", Some(args)) => {
+			let day = parse_days(args.value_of("day").into_iter())?[0];
+			let size = match args.value_of("size") {
+				Some(n) => n.parse()?,
+				None => generate::default_size(day),
+			};
+			let input = generate::generate(day, args.value_of("seed").unwrap().parse()?, size)?;
+			match args.value_of("output") {
+				Some(file) => std::fs::write(file, input)?,
+				None => print!("{}", input),
+			}
+			Ok(())
+		}
+		("lint", Some(args)) => {
+			let day = parse_days(args.value_of("day").into_iter())?[0];
+			let file = args.value_of("file").unwrap();
+			let problems = lint::lint(day, &std::fs::read_to_string(file)?);
+			for p in &problems {
+				println!("{}:{}", file, p);
+			}
+			if !problems.is_empty() {
+				return Err(format!("{} problems in {}", problems.len(), file).into());
+			}
+			Ok(())
+		}
+		("run", Some(args)) => run(args),
+		("watch", Some(args)) => watch::watch(
+			parse_days(args.value_of("day").into_iter())?[0],
+			Duration::from_millis(args.value_of("interval").unwrap().parse()?),
+			args.is_present("record"),
+		),
+		_ => unreachable!(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn days_and_ranges() {
+		let days = |args: &[&str]| parse_days(args.iter().copied()).map_err(|e| e.to_string());
+		assert_eq!(days(&[]).unwrap(), (1..=DAYS).collect::<Vec<_>>());
+		assert_eq!(days(&["7"]).unwrap(), vec![7]);
+		assert_eq!(days(&["day09", "3-5"]).unwrap(), vec![9, 3, 4, 5]);
+		assert_eq!(days(&["4-4"]).unwrap(), vec![4]);
+		assert!(days(&["5-3"]).is_err());
+		assert!(days(&["0"]).is_err());
+		assert!(days(&["3-99"]).is_err());
+		assert!(days(&["x"]).is_err());
+	}
+}
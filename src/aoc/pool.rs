@@ -0,0 +1,64 @@
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// Runs each task on one of n threads and returns the results in the same
+// order as the tasks, no matter which finishes first. Results are passed to
+// each as soon as it and every task before it are done, so the output can be
+// shown as it comes in while staying in order.
+pub fn run<T, R, F, E>(n: usize, tasks: Vec<T>, work: F, mut each: E) -> Vec<R>
+where
+	T: Send + 'static,
+	R: Send + 'static,
+	F: Fn(T) -> R + Send + Sync + 'static,
+	E: FnMut(usize, &R),
+{
+	let count = tasks.len();
+	let queue = Arc::new(Mutex::new(tasks.into_iter().enumerate()));
+	let work = Arc::new(work);
+	let (tx, rx) = mpsc::channel();
+
+	let workers = (0..n.max(1).min(count))
+		.map(|_| {
+			let queue = Arc::clone(&queue);
+			let work = Arc::clone(&work);
+			let tx = tx.clone();
+			thread::spawn(move || loop {
+				// the lock is only held long enough to take the next task
+				let next = queue.lock().unwrap().next();
+				match next {
+					Some((i, task)) => {
+						if tx.send((i, work(task))).is_err() {
+							return;
+						}
+					}
+					None => return,
+				}
+			})
+		})
+		.collect::<Vec<_>>();
+	drop(tx);
+
+	let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+	let mut next = 0;
+	for (i, r) in rx {
+		results[i] = Some(r);
+		while next < count {
+			match &results[next] {
+				Some(r) => each(next, r),
+				None => break,
+			}
+			next += 1;
+		}
+	}
+
+	for w in workers {
+		w.join().expect("worker panicked");
+	}
+
+	// a task that panicked never sent its result
+	results
+		.into_iter()
+		.map(|r| r.expect("task did not finish"))
+		.collect()
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// The last time each file was modified.
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+// Adds every file under path to the snapshot, following directories
+// recursively unless recurse is false. A missing path is left out.
+fn scan(path: &Path, recurse: bool, snapshot: &mut Snapshot) -> io::Result<()> {
+	let entries = match fs::read_dir(path) {
+		Ok(entries) => entries,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+		Err(e) => return Err(e),
+	};
+	for entry in entries {
+		let entry = entry?;
+		let meta = entry.metadata()?;
+		if meta.is_dir() {
+			if recurse {
+				scan(&entry.path(), recurse, snapshot)?;
+			}
+		} else {
+			snapshot.insert(entry.path(), meta.modified()?);
+		}
+	}
+	Ok(())
+}
+
+// The files that can change a day's answers: its own source and data, along
+// with the code shared by every day.
+fn snapshot_of(day: usize) -> io::Result<Snapshot> {
+	let mut snapshot = Snapshot::new();
+	scan(
+		Path::new(&format!("src/day{:02}", day)),
+		true,
+		&mut snapshot,
+	)?;
+	scan(
+		Path::new(&format!("data/day{:02}", day)),
+		true,
+		&mut snapshot,
+	)?;
+	scan(Path::new("src"), false, &mut snapshot)?;
+	Ok(snapshot)
+}
+
+// The inputs for a day, each paired with the file its recorded answers are
+// kept in, like data/day09/example.txt and data/day09/example.expected.
+fn inputs_of(day: usize) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+	let mut inputs = Vec::new();
+	let mut snapshot = Snapshot::new();
+	scan(
+		Path::new(&format!("data/day{:02}", day)),
+		false,
+		&mut snapshot,
+	)?;
+	for path in snapshot.into_keys() {
+		if path.extension().is_some_and(|ext| ext == "txt") {
+			let expected = path.with_extension("expected");
+			inputs.push((path, expected));
+		}
+	}
+	Ok(inputs)
+}
+
+// Rebuilds the day with cargo in the same place and the same way as this
+// runner, so the runner picks up the new executable. Cargo lays its builds
+// out as <target-dir>/<profile>/, where the dev profile's directory is
+// debug, so both come from where this runner is.
+fn build(day: usize) -> Result<(), Box<dyn Error>> {
+	let exe = std::env::current_exe()?;
+	let (profile, target_dir) = match exe
+		.parent()
+		.and_then(|dir| Some((dir.file_name()?.to_str()?, dir.parent()?)))
+	{
+		Some(("debug", target_dir)) => ("dev", target_dir),
+		Some(found) => found,
+		None => return Err(format!("can't tell how {} was built", exe.display()).into()),
+	};
+	let status = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+		.args(["build", "--quiet", "--bin", &format!("day{:02}", day)])
+		.arg("--profile")
+		.arg(profile)
+		.arg("--target-dir")
+		.arg(target_dir)
+		.status()?;
+	if !status.success() {
+		return Err(format!("build failed: {}", status).into());
+	}
+	Ok(())
+}
+
+// Runs the day against one of its inputs and compares the output to the
+// recorded answers, recording them if there are none and record is set.
+fn check(day: usize, input: &Path, expected: &Path, record: bool) -> Result<(), Box<dyn Error>> {
+	let output = Command::new(super::exe_of(day)?).arg(input).output()?;
+	if !output.status.success() {
+		println!("FAIL {}: {}", input.display(), output.status);
+		print!("{}", String::from_utf8_lossy(&output.stderr));
+		return Ok(());
+	}
+	let actual = String::from_utf8_lossy(&output.stdout).into_owned();
+
+	let expected_text = match fs::read_to_string(expected) {
+		Ok(s) => s,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => {
+			if record {
+				fs::write(expected, &actual)?;
+				println!("RECORDED {}", input.display());
+			} else {
+				println!(
+					"NEW {}: no answers in {}",
+					input.display(),
+					expected.display()
+				);
+			}
+			for line in actual.lines() {
+				println!("  {}", line);
+			}
+			return Ok(());
+		}
+		Err(e) => return Err(e.into()),
+	};
+
+	let actual = actual.lines().map(str::trim_end).collect::<Vec<_>>();
+	let expected_lines = expected_text.lines().map(str::trim_end).collect::<Vec<_>>();
+	if actual == expected_lines {
+		println!("PASS {}", input.display());
+		return Ok(());
+	}
+	println!("FAIL {}", input.display());
+	for i in 0..actual.len().max(expected_lines.len()) {
+		let (a, e) = (actual.get(i), expected_lines.get(i));
+		if a == e {
+			println!("  {}", a.unwrap());
+			continue;
+		}
+		if let Some(e) = e {
+			println!("- {}", e);
+		}
+		if let Some(a) = a {
+			println!("+ {}", a);
+		}
+	}
+	Ok(())
+}
+
+// Rebuilds and checks the day every time one of its files changes, polling
+// for changes at the given interval. This never returns unless the files
+// can't be read.
+pub fn watch(day: usize, interval: Duration, record: bool) -> Result<(), Box<dyn Error>> {
+	let mut last = None;
+	loop {
+		let snapshot = snapshot_of(day)?;
+		if last.as_ref() != Some(&snapshot) {
+			let changed = match &last {
+				Some(last) => changes(last, &snapshot),
+				None => Vec::new(),
+			};
+			if changed.is_empty() {
+				println!("== day{:02}", day);
+			} else {
+				println!("== day{:02}: {} changed", day, changed.join(", "));
+			}
+			match build(day) {
+				Ok(()) => {
+					for (input, expected) in inputs_of(day)? {
+						check(day, &input, &expected, record)?;
+					}
+				}
+				Err(e) => println!("{}", e),
+			}
+			// answers that were just recorded count as a change otherwise
+			last = Some(snapshot_of(day)?);
+		}
+		thread::sleep(interval);
+	}
+}
+
+// the files that were added, removed or modified between two snapshots.
+fn changes(before: &Snapshot, after: &Snapshot) -> Vec<String> {
+	let mut changed = after
+		.iter()
+		.filter(|(path, time)| before.get(*path) != Some(time))
+		.map(|(path, _)| path.display().to_string())
+		.collect::<Vec<_>>();
+	changed.extend(
+		before
+			.keys()
+			.filter(|path| !after.contains_key(*path))
+			.map(|path| format!("{} (removed)", path.display())),
+	);
+	changed
+}
@@ -1,3 +1,4 @@
+use aoc::grid::{Grid, Pt};
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fs::File;
@@ -45,9 +46,9 @@ impl Input {
 			}
 			line.clear();
 
-			let mut card = Card::empty();
+			let mut tiles = Vec::with_capacity(25);
 
-			for i in 0..5 {
+			for _ in 0..5 {
 				if r.read_line(&mut line)? == 0 {
 					return Err(Box::new(io::Error::new(
 						io::ErrorKind::UnexpectedEof,
@@ -57,14 +58,15 @@ impl Input {
 
 				for j in 0..5 {
 					let offset = j * 3;
-					let v = line[offset..offset + 2].trim().parse::<u8>()?;
-					card.set(i, j, v);
+					tiles.push(line[offset..offset + 2].trim().parse::<u8>()?);
 				}
 
 				line.clear();
 			}
 
-			cards.push(card);
+			cards.push(Card {
+				tiles: Grid::new(5, tiles)?,
+			});
 		}
 
 		Ok(Input { draws, cards })
@@ -73,17 +75,7 @@ impl Input {
 
 #[derive(Debug)]
 struct Card {
-	tiles: [u8; 25],
-}
-
-impl Card {
-	fn empty() -> Card {
-		Card { tiles: [0; 25] }
-	}
-
-	fn set(&mut self, i: usize, j: usize, num: u8) {
-		self.tiles[i * 5 + j] = num;
-	}
+	tiles: Grid<u8>,
 }
 
 struct Game<'a> {
@@ -92,11 +84,11 @@ struct Game<'a> {
 }
 
 impl<'a> Game<'a> {
-	fn from_input(input: &'a Input) -> Game {
+	fn from_input(input: &'a Input) -> Game<'a> {
 		let cards = input
 			.cards
 			.iter()
-			.map(|c| CardState::new(c))
+			.map(CardState::new)
 			.collect::<Vec<CardState>>();
 		Game {
 			draws: &input.draws,
@@ -105,20 +97,20 @@ impl<'a> Game<'a> {
 	}
 
 	fn play(&mut self) -> Result<Vec<usize>, Box<dyn Error>> {
-		let mut idx: HashMap<u8, Vec<(usize, usize)>> = HashMap::new();
+		let mut idx: HashMap<u8, Vec<(usize, Pt)>> = HashMap::new();
 		let mut has_won = HashSet::new();
 		let mut scores = Vec::new();
 
 		for (i, card) in self.cards.iter().enumerate() {
-			for (j, tile) in card.tiles().iter().enumerate() {
-				idx.entry(*tile).or_default().push((i, j));
+			for (pt, tile) in card.tiles().iter() {
+				idx.entry(*tile).or_default().push((i, pt));
 			}
 		}
 
 		for &draw in self.draws {
-			for (i, j) in idx.entry(draw).or_default().iter() {
-				if self.cards[*i].mark(*j) && has_won.insert(*i) {
-					scores.push(draw as usize * self.cards[*i].sum_unmarked());
+			for &(i, pt) in idx.entry(draw).or_default().iter() {
+				if self.cards[i].mark(pt) && has_won.insert(i) {
+					scores.push(draw as usize * self.cards[i].sum_unmarked());
 					if has_won.len() == self.cards.len() {
 						return Ok(scores);
 					}
@@ -131,42 +123,40 @@ impl<'a> Game<'a> {
 
 struct CardState<'a> {
 	card: &'a Card,
-	marks: [bool; 25],
+	marks: Grid<bool>,
 }
 
 impl<'a> CardState<'a> {
-	fn new(card: &'a Card) -> CardState {
+	fn new(card: &'a Card) -> CardState<'a> {
 		CardState {
-			card: card,
-			marks: [false; 25],
+			card,
+			marks: Grid::filled(card.tiles.width(), card.tiles.height(), false),
 		}
 	}
 
-	fn tiles(&self) -> &[u8] {
+	fn tiles(&self) -> &Grid<u8> {
 		&self.card.tiles
 	}
 
-	fn is_winning_row(&self, idx: usize) -> bool {
-		let ix = (idx / 5) * 5;
-		(ix..ix + 5).find(|&i| !self.marks[i]).is_none()
+	fn is_winning_row(&self, y: usize) -> bool {
+		self.marks.row(y).iter().all(|&marked| marked)
 	}
 
-	fn is_winning_col(&self, idx: usize) -> bool {
-		let ix = idx % 5;
-		(0..5).find(|&i| !self.marks[i * 5 + ix]).is_none()
+	fn is_winning_col(&self, x: usize) -> bool {
+		self.marks.column(x).all(|&marked| marked)
 	}
 
-	fn mark(&mut self, idx: usize) -> bool {
-		self.marks[idx] = true;
-		self.is_winning_row(idx) || self.is_winning_col(idx)
+	fn mark(&mut self, pt: Pt) -> bool {
+		self.marks[pt] = true;
+		let (x, y) = pt;
+		self.is_winning_row(y) || self.is_winning_col(x)
 	}
 
 	fn sum_unmarked(&self) -> usize {
 		self.tiles()
 			.iter()
-			.zip(&self.marks)
-			.filter(|(_, &marked)| !marked)
-			.map(|(&n, _)| n as usize)
+			.filter(|(pt, _)| !self.marks[*pt])
+			.map(|(_, &n)| n as usize)
 			.sum()
 	}
 }
@@ -0,0 +1,139 @@
+use super::{Line, Pt, Raster};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+
+// the largest number of cells we're willing to allocate for a grid.
+const MAX_CELLS: usize = 1 << 28;
+
+// A dense count of how many lines cover each cell within the bounding box of
+// a set of lines. This is much cheaper than hashing points when the
+// coordinates are small. Lines can be anywhere, including at negative
+// coordinates, so the counts are kept on a shared grid relative to the
+// top-left corner of the bounding box.
+#[derive(Debug)]
+pub struct Grid {
+	// the top-left corner of the bounding box
+	min: Pt,
+	counts: aoc::grid::Grid<u16>,
+}
+
+impl Grid {
+	pub fn from_lines<'a, I>(lines: I, raster: Raster) -> Result<Grid, Box<dyn Error>>
+	where
+		I: Iterator<Item = &'a Line> + Clone,
+	{
+		let mut min = Pt::new(isize::MAX, isize::MAX);
+		let mut max = Pt::new(isize::MIN, isize::MIN);
+		for line in lines.clone() {
+			for pt in [line.fr, line.to] {
+				min = Pt::new(min.x.min(pt.x), min.y.min(pt.y));
+				max = Pt::new(max.x.max(pt.x), max.y.max(pt.y));
+			}
+		}
+
+		if min.x > max.x {
+			return Ok(Grid {
+				min: Pt::new(0, 0),
+				counts: aoc::grid::Grid::filled(0, 0, 0),
+			});
+		}
+
+		let width = (max.x - min.x + 1) as usize;
+		let height = (max.y - min.y + 1) as usize;
+		match width.checked_mul(height) {
+			Some(n) if n <= MAX_CELLS => {}
+			_ => return Err(format!("grid too large: {}x{}", width, height).into()),
+		}
+
+		let mut grid = Grid {
+			min,
+			counts: aoc::grid::Grid::filled(width, height, 0),
+		};
+		for line in lines {
+			for pt in line.points(raster) {
+				let at = grid.offset_of(pt);
+				let cell = &mut grid.counts[at];
+				*cell = cell.saturating_add(1);
+			}
+		}
+		Ok(grid)
+	}
+
+	// where pt is on the counts, which start at the top-left corner.
+	fn offset_of(&self, pt: Pt) -> aoc::grid::Pt {
+		((pt.x - self.min.x) as usize, (pt.y - self.min.y) as usize)
+	}
+
+	// every count in row order.
+	fn cells(&self) -> impl Iterator<Item = u16> + '_ {
+		self.counts.rows().flatten().copied()
+	}
+
+	pub fn max(&self) -> u16 {
+		self.cells().max().unwrap_or(0)
+	}
+
+	// the number of cells that are covered by at least k lines.
+	pub fn count_at_least(&self, k: u16) -> usize {
+		self.cells().filter(|&c| c >= k).count()
+	}
+
+	// Writes the counts as a binary PGM where brighter cells have more
+	// overlaps. See http://netpbm.sourceforge.net/doc/pgm.html
+	pub fn write_pgm<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		let max = self.max().max(1);
+		let (width, height) = self.counts.size();
+		write!(w, "P5\n{} {}\n{}\n", width, height, max)?;
+		if max < 256 {
+			w.write_all(&self.cells().map(|c| c as u8).collect::<Vec<_>>())
+		} else {
+			w.write_all(
+				&self
+					.cells()
+					.flat_map(|c| c.to_be_bytes())
+					.collect::<Vec<_>>(),
+			)
+		}
+	}
+
+	// Writes the counts as a binary PPM using a black-red-yellow-white heat
+	// ramp. See http://netpbm.sourceforge.net/doc/ppm.html
+	pub fn write_ppm<W: Write>(&self, w: &mut W) -> io::Result<()> {
+		let max = self.max().max(1) as usize;
+		let (width, height) = self.counts.size();
+		write!(w, "P6\n{} {}\n255\n", width, height)?;
+		w.write_all(
+			&self
+				.cells()
+				.flat_map(|c| heat(c as usize * 765 / max))
+				.collect::<Vec<_>>(),
+		)
+	}
+}
+
+fn heat(v: usize) -> [u8; 3] {
+	match v {
+		0..=255 => [v as u8, 0, 0],
+		256..=510 => [255, (v - 255) as u8, 0],
+		_ => [255, 255, (v - 510).min(255) as u8],
+	}
+}
+
+// Renders the grid like the puzzle's illustration, with a '.' for cells that
+// no line covers and the number of lines otherwise.
+impl fmt::Display for Grid {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for row in self.counts.rows() {
+			for &c in row {
+				match c {
+					0 => write!(f, ".")?,
+					1..=9 => write!(f, "{}", c)?,
+					_ => write!(f, "+")?,
+				}
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,244 @@
+use super::{Line, Orientation, Pt, Raster};
+use std::collections::{BTreeSet, HashMap};
+
+// The lanes a line's points are grouped into. Each line is indexed in the
+// family that keeps its points together, so a line in any of the puzzle's
+// directions is a single span no matter how long it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Family {
+	// lanes are rows and positions are x
+	Row,
+	// lanes are columns and positions are y
+	Column,
+	// lanes are x - y and positions are x
+	Diagonal,
+	// lanes are x + y and positions are x
+	AntiDiagonal,
+}
+
+const FAMILIES: [Family; 4] = [
+	Family::Row,
+	Family::Column,
+	Family::Diagonal,
+	Family::AntiDiagonal,
+];
+
+impl Family {
+	// Lines that are mostly horizontal have few runs per row and lines that
+	// are mostly vertical have few runs per column.
+	fn of(line: &Line) -> Family {
+		let dx = line.to.x - line.fr.x;
+		let dy = line.to.y - line.fr.y;
+		match line.orientation() {
+			Orientation::Point | Orientation::Horizontal => Family::Row,
+			Orientation::Vertical => Family::Column,
+			Orientation::Diagonal if dx == dy => Family::Diagonal,
+			Orientation::Diagonal => Family::AntiDiagonal,
+			Orientation::Oblique if dx.abs() >= dy.abs() => Family::Row,
+			Orientation::Oblique => Family::Column,
+		}
+	}
+
+	// the lane a point is in and its position along that lane.
+	fn place(self, pt: Pt) -> (isize, isize) {
+		match self {
+			Family::Row => (pt.y, pt.x),
+			Family::Column => (pt.x, pt.y),
+			Family::Diagonal => (pt.x - pt.y, pt.x),
+			Family::AntiDiagonal => (pt.x + pt.y, pt.x),
+		}
+	}
+
+	// the first and last rows that a span in the given lane covers.
+	fn rows(self, lane: isize, span: &Span) -> (isize, isize) {
+		match self {
+			Family::Row => (lane, lane),
+			Family::Column => (span.lo, span.hi),
+			Family::Diagonal => (span.lo - lane, span.hi - lane),
+			Family::AntiDiagonal => (lane - span.hi, lane - span.lo),
+		}
+	}
+
+	// the first and last columns that a span in the given lane covers in row
+	// y, which must be one of its rows.
+	fn columns_at(self, lane: isize, span: &Span, y: isize) -> (isize, isize) {
+		match self {
+			Family::Row => (span.lo, span.hi),
+			Family::Column => (lane, lane),
+			Family::Diagonal => (lane + y, lane + y),
+			Family::AntiDiagonal => (lane - y, lane - y),
+		}
+	}
+}
+
+// A run of consecutive points in a single lane that belong to one line.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+	lo: isize,
+	hi: isize,
+	line: usize,
+}
+
+// Splits the points of a line into spans of consecutive positions in lanes of
+// the family it is indexed in.
+fn spans_of(line: &Line, family: Family, raster: Raster) -> Vec<(isize, isize, isize)> {
+	let mut spans: Vec<(isize, isize, isize)> = Vec::new();
+	for pt in line.points(raster) {
+		let (lane, pos) = family.place(pt);
+		match spans.last_mut() {
+			Some((l, _, hi)) if *l == lane && *hi + 1 == pos => *hi = pos,
+			Some((l, lo, _)) if *l == lane && *lo - 1 == pos => *lo = pos,
+			_ => spans.push((lane, pos, pos)),
+		}
+	}
+	spans
+}
+
+// A spatial index over a set of lines that keeps, for each lane of rows,
+// columns and both diagonals, the spans each line covers in that lane.
+#[derive(Debug)]
+pub struct Index<'a> {
+	lines: &'a [Line],
+	raster: Raster,
+	lanes: HashMap<(Family, isize), Vec<Span>>,
+}
+
+impl<'a> Index<'a> {
+	pub fn new(lines: &'a [Line], raster: Raster) -> Index<'a> {
+		let mut lanes: HashMap<(Family, isize), Vec<Span>> = HashMap::new();
+		for (i, line) in lines.iter().enumerate() {
+			let family = Family::of(line);
+			for (lane, lo, hi) in spans_of(line, family, raster) {
+				lanes
+					.entry((family, lane))
+					.or_default()
+					.push(Span { lo, hi, line: i });
+			}
+		}
+		for spans in lanes.values_mut() {
+			spans.sort_unstable_by_key(|s| (s.lo, s.hi, s.line));
+		}
+		Index {
+			lines,
+			raster,
+			lanes,
+		}
+	}
+
+	pub fn line(&self, i: usize) -> &'a Line {
+		&self.lines[i]
+	}
+
+	// adds the lines that pass through the given point to found.
+	fn find_through(&self, pt: Pt, found: &mut BTreeSet<usize>) {
+		for family in FAMILIES {
+			let (lane, pos) = family.place(pt);
+			if let Some(spans) = self.lanes.get(&(family, lane)) {
+				for span in spans.iter().take_while(|s| s.lo <= pos) {
+					if pos <= span.hi {
+						found.insert(span.line);
+					}
+				}
+			}
+		}
+	}
+
+	// the indexes of the lines that pass through the given point.
+	pub fn lines_through(&self, pt: Pt) -> Vec<usize> {
+		let mut found = BTreeSet::new();
+		self.find_through(pt, &mut found);
+		found.into_iter().collect()
+	}
+
+	// the indexes of the lines that share at least one point with the given
+	// line. The line does not have to be one of the indexed lines.
+	pub fn lines_crossing(&self, line: &Line) -> Vec<usize> {
+		let mut found = BTreeSet::new();
+		for pt in line.points(self.raster) {
+			self.find_through(pt, &mut found);
+		}
+		found.into_iter().collect()
+	}
+
+	// the top-left and bottom-right corners of the box containing every line.
+	pub fn bounds(&self) -> Option<(Pt, Pt)> {
+		self.lines
+			.iter()
+			.flat_map(|l| [l.fr, l.to])
+			.fold(None, |b, pt| match b {
+				None => Some((pt, pt)),
+				Some((min, max)) => Some((
+					Pt::new(min.x.min(pt.x), min.y.min(pt.y)),
+					Pt::new(max.x.max(pt.x), max.y.max(pt.y)),
+				)),
+			})
+	}
+
+	// The largest number of lines covering a single point along with every
+	// point that is covered by that many lines, ordered by row then column.
+	pub fn max_overlap(&self) -> (usize, Vec<Pt>) {
+		let mut max = 0;
+		let mut pts = Vec::new();
+
+		// every span by the first row it covers, so the rows can be swept in
+		// order while only keeping the spans that cover the current row
+		let mut spans = self
+			.lanes
+			.iter()
+			.flat_map(|(&(family, lane), spans)| {
+				spans.iter().map(move |span| {
+					let (top, bottom) = family.rows(lane, span);
+					(top, bottom, family, lane, span)
+				})
+			})
+			.collect::<Vec<_>>();
+		spans.sort_unstable_by_key(|s| s.0);
+
+		let mut active = Vec::new();
+		let mut next = 0;
+		let mut events = Vec::new();
+		let mut y = spans.first().map_or(0, |s| s.0);
+		while next < spans.len() || !active.is_empty() {
+			if active.is_empty() {
+				y = y.max(spans[next].0);
+			}
+			while next < spans.len() && spans[next].0 <= y {
+				active.push(spans[next]);
+				next += 1;
+			}
+
+			events.clear();
+			for &(_, _, family, lane, span) in &active {
+				let (lo, hi) = family.columns_at(lane, span, y);
+				events.push((lo, 1));
+				events.push((hi + 1, -1));
+			}
+			events.sort_unstable();
+
+			let mut depth = 0_isize;
+			let mut i = 0;
+			while i < events.len() {
+				let x = events[i].0;
+				while i < events.len() && events[i].0 == x {
+					depth += events[i].1;
+					i += 1;
+				}
+				if depth <= 0 || (depth as usize) < max {
+					continue;
+				}
+				if depth as usize > max {
+					max = depth as usize;
+					pts.clear();
+				}
+				// the depth holds until the next event
+				let end = events.get(i).map_or(x + 1, |e| e.0);
+				pts.extend((x..end).map(|x| Pt::new(x, y)));
+			}
+
+			active.retain(|s| s.1 > y);
+			y += 1;
+		}
+
+		(max, pts)
+	}
+}
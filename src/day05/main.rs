@@ -1,9 +1,14 @@
 use std::collections::HashSet;
 use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, BufWriter};
 use std::str::FromStr;
 
+mod grid;
+mod index;
+mod sweep;
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 struct Pt {
 	x: isize,
@@ -16,41 +21,144 @@ impl Pt {
 	}
 }
 
-struct Pts {
-	fr: Pt,
-	to: Pt,
-	done: bool,
+impl FromStr for Pt {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match bisect(s, ",") {
+			Some((x, y)) => Ok(Pt::new(x.parse::<isize>()?, y.parse::<isize>()?)),
+			None => Err(format!("invalid pt: {}", s).into()),
+		}
+	}
+}
+
+impl fmt::Display for Pt {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{},{}", self.x, self.y)
+	}
 }
 
-fn step(fr: isize, to: isize) -> isize {
-	if fr == to {
-		0
-	} else if fr > to {
-		-1
+fn gcd(a: isize, b: isize) -> isize {
+	if b == 0 {
+		a.abs()
 	} else {
-		1
+		gcd(b, a % b)
 	}
 }
 
+// Iterates over the lattice points that lie exactly on a line segment. The
+// segment is walked in steps of (dx/g, dy/g), where g is the gcd of the
+// deltas, so every point emitted has integer coordinates on the true line.
+struct Pts {
+	pt: Pt,
+	step: Pt,
+	remaining: usize,
+}
+
 impl Iterator for Pts {
 	type Item = Pt;
 
 	fn next(&mut self) -> Option<Pt> {
-		if self.done {
+		if self.remaining == 0 {
 			None
 		} else {
-			let Pt { x: xa, y: ya } = self.fr;
-			let Pt { x: xb, y: yb } = self.to;
-			self.done = &self.to == &self.fr;
-			self.fr = Pt {
-				x: xa + step(xa, xb),
-				y: ya + step(ya, yb),
-			};
-			Some(Pt { x: xa, y: ya })
+			let pt = self.pt;
+			self.pt = Pt::new(pt.x + self.step.x, pt.y + self.step.y);
+			self.remaining -= 1;
+			Some(pt)
 		}
 	}
 }
 
+// Iterates over the cells visited by Bresenham's line algorithm, which covers
+// every column (or row) the segment passes through even when the segment
+// doesn't hit lattice points there.
+// See https://en.wikipedia.org/wiki/Bresenham%27s_line_algorithm#All_cases
+struct Bresenham {
+	pt: Pt,
+	to: Pt,
+	dx: isize,
+	dy: isize,
+	sx: isize,
+	sy: isize,
+	err: isize,
+	done: bool,
+}
+
+impl Iterator for Bresenham {
+	type Item = Pt;
+
+	fn next(&mut self) -> Option<Pt> {
+		if self.done {
+			return None;
+		}
+
+		let pt = self.pt;
+		if pt == self.to {
+			self.done = true;
+		} else {
+			let e2 = 2 * self.err;
+			if e2 >= self.dy {
+				self.err += self.dy;
+				self.pt.x += self.sx;
+			}
+			if e2 <= self.dx {
+				self.err += self.dx;
+				self.pt.y += self.sy;
+			}
+		}
+		Some(pt)
+	}
+}
+
+enum Points {
+	Exact(Pts),
+	Bresenham(Bresenham),
+}
+
+impl Iterator for Points {
+	type Item = Pt;
+
+	fn next(&mut self) -> Option<Pt> {
+		match self {
+			Points::Exact(pts) => pts.next(),
+			Points::Bresenham(pts) => pts.next(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Raster {
+	// only the lattice points that lie exactly on the segment
+	Exact,
+	// every cell Bresenham's algorithm visits between the end points
+	Bresenham,
+}
+
+impl FromStr for Raster {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"exact" => Ok(Raster::Exact),
+			"bresenham" => Ok(Raster::Bresenham),
+			_ => Err(format!("invalid raster: {}", s).into()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Orientation {
+	// both end points are the same
+	Point,
+	Horizontal,
+	Vertical,
+	// exactly 45°
+	Diagonal,
+	// any other slope
+	Oblique,
+}
+
 #[derive(Debug)]
 struct Line {
 	fr: Pt,
@@ -62,15 +170,51 @@ impl Line {
 		Line { fr, to }
 	}
 
+	fn orientation(&self) -> Orientation {
+		let dx = self.to.x - self.fr.x;
+		let dy = self.to.y - self.fr.y;
+		if dx == 0 && dy == 0 {
+			Orientation::Point
+		} else if dy == 0 {
+			Orientation::Horizontal
+		} else if dx == 0 {
+			Orientation::Vertical
+		} else if dx.abs() == dy.abs() {
+			Orientation::Diagonal
+		} else {
+			Orientation::Oblique
+		}
+	}
+
 	fn is_parallel_to_axis(&self) -> bool {
-		self.fr.x == self.to.x || self.fr.y == self.to.y
+		matches!(
+			self.orientation(),
+			Orientation::Point | Orientation::Horizontal | Orientation::Vertical
+		)
 	}
 
-	fn points(&self) -> Pts {
-		Pts {
-			fr: self.fr,
-			to: self.to,
-			done: false,
+	fn points(&self, raster: Raster) -> Points {
+		let dx = self.to.x - self.fr.x;
+		let dy = self.to.y - self.fr.y;
+		match raster {
+			Raster::Exact => {
+				let g = gcd(dx, dy).max(1);
+				Points::Exact(Pts {
+					pt: self.fr,
+					step: Pt::new(dx / g, dy / g),
+					remaining: gcd(dx, dy) as usize + 1,
+				})
+			}
+			Raster::Bresenham => Points::Bresenham(Bresenham {
+				pt: self.fr,
+				to: self.to,
+				dx: dx.abs(),
+				dy: -dy.abs(),
+				sx: dx.signum(),
+				sy: dy.signum(),
+				err: dx.abs() - dy.abs(),
+				done: false,
+			}),
 		}
 	}
 }
@@ -88,17 +232,13 @@ impl FromStr for Line {
 			None => return Err(format!("invalid line: {}", s).into()),
 		};
 
-		let fr = match bisect(fr, ",") {
-			Some((x, y)) => Pt::new(x.parse::<isize>()?, y.parse::<isize>()?),
-			None => return Err(format!("invalid pt: {}", fr).into()),
-		};
-
-		let to = match bisect(to, ",") {
-			Some((x, y)) => Pt::new(x.parse::<isize>()?, y.parse::<isize>()?),
-			None => return Err(format!("invalid pt: {}", to).into()),
-		};
+		Ok(Line::new(fr.parse()?, to.parse()?))
+	}
+}
 
-		Ok(Line::new(fr, to))
+impl fmt::Display for Line {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} -> {}", self.fr, self.to)
 	}
 }
 
@@ -111,14 +251,14 @@ fn lines_from_reader<R: io::Read>(r: R) -> Result<Vec<Line>, Box<dyn Error>> {
 	Ok(lines)
 }
 
-fn intersections_of<'a, I>(lines: I) -> HashSet<Pt>
+fn intersections_of<'a, I>(lines: I, raster: Raster) -> HashSet<Pt>
 where
 	I: Iterator<Item = &'a Line>,
 {
 	let mut points = HashSet::new();
 	let mut intersections = HashSet::new();
 	for line in lines {
-		for pt in line.points() {
+		for pt in line.points(raster) {
 			if !points.insert(pt) {
 				intersections.insert(pt);
 			}
@@ -127,6 +267,90 @@ where
 	intersections
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+	// rasterize every line into a set of points
+	HashSet,
+	// count overlaps per family of parallel lines without visiting each point
+	Sweep,
+	// count overlaps in a dense grid covering the bounding box of the lines
+	Grid,
+}
+
+impl FromStr for Method {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"hashset" => Ok(Method::HashSet),
+			"sweep" => Ok(Method::Sweep),
+			"grid" => Ok(Method::Grid),
+			_ => Err(format!("invalid method: {}", s).into()),
+		}
+	}
+}
+
+fn count_intersections<'a, I>(
+	lines: I,
+	method: Method,
+	raster: Raster,
+	check: bool,
+) -> Result<usize, Box<dyn Error>>
+where
+	I: Iterator<Item = &'a Line> + Clone,
+{
+	if method == Method::Sweep && raster != Raster::Exact {
+		return Err("the sweep method only supports the exact raster".into());
+	}
+
+	let n = match method {
+		Method::HashSet => intersections_of(lines.clone(), raster).len(),
+		Method::Sweep => sweep::count_intersections(lines.clone()),
+		Method::Grid => grid::Grid::from_lines(lines.clone(), raster)?.count_at_least(2),
+	};
+
+	if check {
+		let expected = intersections_of(lines, raster).len();
+		if n != expected {
+			return Err(format!(
+				"{:?} found {} intersections, expected {}",
+				method, n, expected
+			)
+			.into());
+		}
+	}
+
+	Ok(n)
+}
+
+fn query(index: &index::Index, name: &str, args: &clap::ArgMatches) -> Result<(), Box<dyn Error>> {
+	let found = match name {
+		"through" => index.lines_through(args.value_of("pt").unwrap().parse()?),
+		"crossing" => index.lines_crossing(&args.value_of("line").unwrap().parse()?),
+		"bounds" => {
+			match index.bounds() {
+				Some((min, max)) => println!("{} -> {}", min, max),
+				None => println!("no lines"),
+			}
+			return Ok(());
+		}
+		"max" => {
+			let (n, pts) = index.max_overlap();
+			println!("{} lines cover {} points", n, pts.len());
+			for pt in pts {
+				println!("{}", pt);
+			}
+			return Ok(());
+		}
+		_ => return Err(format!("invalid query: {}", name).into()),
+	};
+
+	for i in found {
+		println!("{}: {}", i, index.line(i));
+	}
+	Ok(())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
 	let matches = clap::App::new("day05")
 		.arg(
@@ -134,16 +358,122 @@ fn main() -> Result<(), Box<dyn Error>> {
 				.takes_value(true)
 				.help("the input file"),
 		)
+		.arg(
+			clap::Arg::with_name("raster")
+				.long("raster")
+				.takes_value(true)
+				.possible_values(&["exact", "bresenham"])
+				.default_value("exact")
+				.help("how lines that aren't horizontal, vertical or diagonal are rasterized"),
+		)
+		.arg(
+			clap::Arg::with_name("method")
+				.long("method")
+				.takes_value(true)
+				.possible_values(&["hashset", "sweep", "grid"])
+				.default_value("hashset")
+				.help("how intersections are counted"),
+		)
+		.arg(
+			clap::Arg::with_name("check")
+				.long("check")
+				.help("cross-check the count against the hashset method"),
+		)
+		.arg(
+			clap::Arg::with_name("at-least")
+				.long("at-least")
+				.takes_value(true)
+				.help("also report the number of points covered by at least this many lines"),
+		)
+		.arg(
+			clap::Arg::with_name("heatmap")
+				.long("heatmap")
+				.help("print the number of lines covering each point"),
+		)
+		.arg(
+			clap::Arg::with_name("pgm")
+				.long("pgm")
+				.takes_value(true)
+				.help("write the overlap counts to a grayscale PGM image"),
+		)
+		.arg(
+			clap::Arg::with_name("ppm")
+				.long("ppm")
+				.takes_value(true)
+				.help("write the overlap counts to a color PPM image"),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("through")
+				.about("list the lines that pass through a point")
+				.arg(
+					clap::Arg::with_name("pt")
+						.required(true)
+						.help("the point, as x,y"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("crossing")
+				.about("list the lines that share a point with a line")
+				.arg(
+					clap::Arg::with_name("line")
+						.required(true)
+						.help("the line, as \"x1,y1 -> x2,y2\""),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("bounds").about("show the bounding box of all lines"),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("max").about("list the points covered by the most lines"),
+		)
 		.get_matches();
 
+	let raster = matches.value_of("raster").unwrap().parse::<Raster>()?;
+	let method = matches.value_of("method").unwrap().parse::<Method>()?;
+	let check = matches.is_present("check");
+
 	let lines = lines_from_reader(File::open(
 		matches.value_of("input").unwrap_or("data/day05/input.txt"),
 	)?)?;
 
-	println!(
-		"Part 1: {}",
-		intersections_of(lines.iter().filter(|l| l.is_parallel_to_axis())).len()
+	if let (name, Some(args)) = matches.subcommand() {
+		return query(&index::Index::new(&lines, raster), name, args);
+	}
+
+	// the parts only share the lines, so they're counted at the same time
+	let (part1, part2) = aoc::parts::both(
+		|| {
+			count_intersections(
+				lines.iter().filter(|l| l.is_parallel_to_axis()),
+				method,
+				raster,
+				check,
+			)
+			.map_err(|e| e.to_string())
+		},
+		|| count_intersections(lines.iter(), method, raster, check).map_err(|e| e.to_string()),
 	);
-	println!("Part 2: {}", intersections_of(lines.iter()).len());
+	println!("Part 1: {}", part1?);
+	println!("Part 2: {}", part2?);
+
+	let wants_grid = ["at-least", "heatmap", "pgm", "ppm"]
+		.iter()
+		.any(|&name| matches.is_present(name));
+	if wants_grid {
+		let grid = grid::Grid::from_lines(lines.iter(), raster)?;
+		if let Some(k) = matches.value_of("at-least") {
+			let k = k.parse::<u16>()?;
+			println!("At least {}: {}", k, grid.count_at_least(k));
+		}
+		if matches.is_present("heatmap") {
+			print!("{}", grid);
+		}
+		if let Some(path) = matches.value_of("pgm") {
+			grid.write_pgm(&mut BufWriter::new(File::create(path)?))?;
+		}
+		if let Some(path) = matches.value_of("ppm") {
+			grid.write_ppm(&mut BufWriter::new(File::create(path)?))?;
+		}
+	}
 	Ok(())
 }
@@ -0,0 +1,173 @@
+use super::{gcd, Line, Pt};
+use std::collections::{HashMap, HashSet};
+
+// Every lattice point on a line whose reduced direction is (a, b) satisfies
+// b*x - a*y = c for some constant c, and consecutive lattice points are
+// exactly (a, b) apart. Lines are grouped into families by direction and
+// then by c, which turns the overlaps within a family into a 1-d interval
+// problem over the step index along the line.
+type Dir = (isize, isize);
+
+fn direction_of(line: &Line) -> Dir {
+	let dx = line.to.x - line.fr.x;
+	let dy = line.to.y - line.fr.y;
+	let g = gcd(dx, dy);
+	if g == 0 {
+		// a single point behaves like a horizontal line of length 0
+		return (1, 0);
+	}
+	let (a, b) = (dx / g, dy / g);
+	if a < 0 || (a == 0 && b < 0) {
+		(-a, -b)
+	} else {
+		(a, b)
+	}
+}
+
+fn key_of((a, b): Dir, pt: Pt) -> isize {
+	b * pt.x - a * pt.y
+}
+
+// the step index of a lattice point along a line of the given direction.
+fn index_of((a, b): Dir, pt: Pt) -> isize {
+	if a != 0 {
+		pt.x.div_euclid(a)
+	} else {
+		pt.y.div_euclid(b)
+	}
+}
+
+#[derive(Debug, Default)]
+struct Runs {
+	// sorted, disjoint ranges covered by at least one line
+	covered: Vec<(isize, isize)>,
+	// sorted, disjoint ranges covered by at least two lines
+	overlapped: Vec<(isize, isize)>,
+}
+
+fn contains(ranges: &[(isize, isize)], t: isize) -> bool {
+	let ix = ranges.partition_point(|&(_, hi)| hi < t);
+	ix < ranges.len() && ranges[ix].0 <= t
+}
+
+impl Runs {
+	fn from_intervals(intervals: &[(isize, isize)]) -> Runs {
+		let mut events = Vec::with_capacity(intervals.len() * 2);
+		for &(lo, hi) in intervals {
+			events.push((lo, 1));
+			events.push((hi + 1, -1));
+		}
+		events.sort_unstable();
+
+		let mut runs = Runs::default();
+		let mut depth = 0;
+		let mut i = 0;
+		while i < events.len() {
+			let t = events[i].0;
+			let prev = depth;
+			while i < events.len() && events[i].0 == t {
+				depth += events[i].1;
+				i += 1;
+			}
+			if prev < 1 && depth >= 1 {
+				runs.covered.push((t, t));
+			} else if prev >= 1 && depth < 1 {
+				runs.covered.last_mut().unwrap().1 = t - 1;
+			}
+			if prev < 2 && depth >= 2 {
+				runs.overlapped.push((t, t));
+			} else if prev >= 2 && depth < 2 {
+				runs.overlapped.last_mut().unwrap().1 = t - 1;
+			}
+		}
+		runs
+	}
+
+	fn overlapped_len(&self) -> usize {
+		self.overlapped
+			.iter()
+			.map(|&(lo, hi)| (hi - lo + 1) as usize)
+			.sum()
+	}
+}
+
+// Counts the lattice points covered by at least two lines without visiting
+// every point on every line. Only the exact lattice points of each segment
+// are considered, so this agrees with `intersections_of` using
+// `Raster::Exact`.
+pub fn count_intersections<'a, I>(lines: I) -> usize
+where
+	I: Iterator<Item = &'a Line>,
+{
+	let mut families: HashMap<Dir, HashMap<isize, Vec<(isize, isize)>>> = HashMap::new();
+	for line in lines {
+		let dir = direction_of(line);
+		let (ta, tb) = (index_of(dir, line.fr), index_of(dir, line.to));
+		families
+			.entry(dir)
+			.or_default()
+			.entry(key_of(dir, line.fr))
+			.or_default()
+			.push((ta.min(tb), ta.max(tb)));
+	}
+
+	let families = families
+		.into_iter()
+		.map(|(dir, keys)| {
+			let runs = keys
+				.into_iter()
+				.map(|(c, intervals)| (c, Runs::from_intervals(&intervals)))
+				.collect::<HashMap<_, _>>();
+			(dir, runs)
+		})
+		.collect::<Vec<_>>();
+
+	let mut count = families
+		.iter()
+		.flat_map(|(_, runs)| runs.values())
+		.map(Runs::overlapped_len)
+		.sum::<usize>();
+
+	// Points where lines from different families cross. These are either new
+	// intersections or points already counted as overlaps in one or more
+	// families, which need to be counted exactly once.
+	let mut crossings = HashSet::new();
+	for (i, (da, ra)) in families.iter().enumerate() {
+		for (db, rb) in &families[i + 1..] {
+			let (a1, b1) = *da;
+			let (a2, b2) = *db;
+			let det = a1 * b2 - a2 * b1;
+			for (&c1, runs_a) in ra {
+				for (&c2, runs_b) in rb {
+					let (xn, yn) = (a1 * c2 - a2 * c1, b1 * c2 - b2 * c1);
+					if xn % det != 0 || yn % det != 0 {
+						continue;
+					}
+					let pt = Pt::new(xn / det, yn / det);
+					if contains(&runs_a.covered, index_of(*da, pt))
+						&& contains(&runs_b.covered, index_of(*db, pt))
+					{
+						crossings.insert(pt);
+					}
+				}
+			}
+		}
+	}
+
+	for pt in crossings {
+		let n = families
+			.iter()
+			.filter(|(dir, runs)| match runs.get(&key_of(*dir, pt)) {
+				Some(runs) => contains(&runs.overlapped, index_of(*dir, pt)),
+				None => false,
+			})
+			.count();
+		if n == 0 {
+			count += 1;
+		} else {
+			count -= n - 1;
+		}
+	}
+
+	count
+}
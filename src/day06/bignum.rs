@@ -0,0 +1,98 @@
+use std::fmt;
+
+// An arbitrary-precision unsigned integer, just large enough to support the
+// additions and multiplications needed to count fish. Limbs are stored least
+// significant first with no trailing zero limbs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigUint {
+	limbs: Vec<u32>,
+}
+
+impl BigUint {
+	pub fn from_u64(v: u64) -> BigUint {
+		let mut n = BigUint {
+			limbs: vec![v as u32, (v >> 32) as u32],
+		};
+		n.normalize();
+		n
+	}
+
+	fn normalize(&mut self) {
+		while self.limbs.last() == Some(&0) {
+			self.limbs.pop();
+		}
+	}
+
+	fn is_zero(&self) -> bool {
+		self.limbs.is_empty()
+	}
+
+	pub fn add(&self, o: &BigUint) -> BigUint {
+		let n = self.limbs.len().max(o.limbs.len());
+		let mut limbs = Vec::with_capacity(n + 1);
+		let mut carry = 0_u64;
+		for i in 0..n {
+			let a = *self.limbs.get(i).unwrap_or(&0) as u64;
+			let b = *o.limbs.get(i).unwrap_or(&0) as u64;
+			let s = a + b + carry;
+			limbs.push(s as u32);
+			carry = s >> 32;
+		}
+		limbs.push(carry as u32);
+		let mut n = BigUint { limbs };
+		n.normalize();
+		n
+	}
+
+	pub fn mul(&self, o: &BigUint) -> BigUint {
+		if self.is_zero() || o.is_zero() {
+			return BigUint { limbs: Vec::new() };
+		}
+		let mut limbs = vec![0_u32; self.limbs.len() + o.limbs.len()];
+		for (i, &a) in self.limbs.iter().enumerate() {
+			let mut carry = 0_u64;
+			for (j, &b) in o.limbs.iter().enumerate() {
+				let t = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+				limbs[i + j] = t as u32;
+				carry = t >> 32;
+			}
+			limbs[i + o.limbs.len()] = carry as u32;
+		}
+		let mut n = BigUint { limbs };
+		n.normalize();
+		n
+	}
+
+	// divides in place by a small divisor, returning the remainder.
+	fn div_rem_small(&mut self, d: u32) -> u32 {
+		let mut rem = 0_u64;
+		for limb in self.limbs.iter_mut().rev() {
+			let cur = (rem << 32) | *limb as u64;
+			*limb = (cur / d as u64) as u32;
+			rem = cur % d as u64;
+		}
+		self.normalize();
+		rem as u32
+	}
+}
+
+impl fmt::Display for BigUint {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.is_zero() {
+			return write!(f, "0");
+		}
+
+		// peel off base 10^9 digits, least significant first
+		let mut n = self.clone();
+		let mut chunks = Vec::new();
+		while !n.is_zero() {
+			chunks.push(n.div_rem_small(1_000_000_000));
+		}
+
+		write!(f, "{}", chunks.last().unwrap())?;
+		for chunk in chunks.iter().rev().skip(1) {
+			write!(f, "{:09}", chunk)?;
+		}
+		Ok(())
+	}
+}
@@ -1,38 +1,452 @@
+use bignum::BigUint;
+use series::Snapshot;
 use std::error::Error;
-use std::fs::read_to_string;
+use std::fmt::Display;
+use std::fs::{read_to_string, File};
+use std::io::{self, BufWriter, Write};
 use std::str::FromStr;
 
+mod bignum;
+mod series;
+
+// The rules that govern the life of a fish. Every fish starts life with a
+// timer of `initial` days, though a newborn waits an extra `maturation` days
+// before its timer begins. Whenever a timer passes 0, the fish spawns a new
+// fish and its timer resets to `reset`. If there is a `lifespan`, fish die
+// once they reach that age in days.
+#[derive(Debug, Clone, Copy)]
+struct Lifecycle {
+	initial: usize,
+	reset: usize,
+	maturation: usize,
+	lifespan: Option<usize>,
+}
+
+impl Default for Lifecycle {
+	fn default() -> Self {
+		Lifecycle {
+			initial: 8,
+			reset: 6,
+			maturation: 0,
+			lifespan: None,
+		}
+	}
+}
+
+impl Lifecycle {
+	// Reads a config with one `name = value` per line. Blank lines and lines
+	// starting with # are ignored and missing values keep their defaults.
+	fn from_config(s: &str) -> Result<Lifecycle, Box<dyn Error>> {
+		let mut lc = Lifecycle::default();
+		for (i, line) in s.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let (name, value) = match line.split_once('=') {
+				Some((name, value)) => (name.trim(), value.trim()),
+				None => return Err(format!("line {}: invalid setting: {}", i + 1, line).into()),
+			};
+			lc.set(name, value)
+				.map_err(|e| format!("line {}: {}", i + 1, e))?;
+		}
+		Ok(lc)
+	}
+
+	fn set(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+		let v = value.parse::<usize>()?;
+		match name {
+			"initial" => self.initial = v,
+			"reset" => self.reset = v,
+			"maturation" => self.maturation = v,
+			"lifespan" => self.lifespan = Some(v),
+			_ => return Err(format!("unknown setting: {}", name).into()),
+		}
+		Ok(())
+	}
+
+	// the age at which a newborn's timer first reaches 0.
+	fn first_spawn(&self) -> usize {
+		self.maturation + self.initial
+	}
+
+	// The number of ages a school needs to track. Without a lifespan, fish
+	// older than a full cycle past their first spawn are indistinguishable
+	// from younger adults so the oldest age wraps back around.
+	fn ages(&self) -> usize {
+		match self.lifespan {
+			Some(n) => n,
+			None => self.first_spawn() + self.reset + 1,
+		}
+	}
+
+	fn spawns_at(&self, age: usize) -> bool {
+		let first = self.first_spawn();
+		age >= first && (age - first) % (self.reset + 1) == 0
+	}
+}
+
 #[derive(Debug)]
 struct School {
-	// circular buffer with counts per generation
-	generations: [usize; 9],
+	lifecycle: Lifecycle,
+	// circular buffer with counts per age
+	generations: Vec<usize>,
 	// current time
 	t: usize,
 }
 
 impl School {
-	fn simulate_until(&mut self, t: usize) -> usize {
-		while self.t < t {
-			let spawning_gen = self.t % 9;
-			let spawned_into_gen = (self.t + 7) % 9;
-			self.generations[spawned_into_gen] += self.generations[spawning_gen];
+	// Creates a school from the timers of each fish. Fish are assumed to be
+	// as old as a newborn would be when its timer reads the same value.
+	fn new(lifecycle: Lifecycle, timers: &[usize]) -> Result<School, Box<dyn Error>> {
+		let n = lifecycle.ages();
+		let first = lifecycle.first_spawn();
+		let mut generations = vec![0; n];
+		for &timer in timers {
+			if timer > first {
+				return Err(format!("timer {} exceeds the newborn timer {}", timer, first).into());
+			}
+			let age = first - timer;
+			if age < n {
+				generations[age] += 1;
+			}
+		}
+		Ok(School {
+			lifecycle,
+			generations,
+			t: 0,
+		})
+	}
+
+	fn slot(&self, age: usize, t: usize) -> usize {
+		let n = self.generations.len();
+		(age + n - t % n) % n
+	}
+
+	// Steps through each day until time t and returns the population then.
+	// This fails once the population no longer fits in a usize, at which
+	// point population_at can still count it.
+	fn simulate_until(&mut self, t: usize) -> Result<usize, Box<dyn Error>> {
+		let n = self.generations.len();
+		let lc = self.lifecycle;
+		while self.t < t && n > 0 {
+			let mut spawned = 0usize;
+			for age in (0..n).filter(|&age| lc.spawns_at(age)) {
+				spawned = spawned
+					.checked_add(self.generations[self.slot(age, self.t)])
+					.ok_or_else(|| too_many(self.t + 1))?;
+			}
+			// the oldest fish will occupy the slot for newborns tomorrow
+			let oldest = self.slot(n - 1, self.t);
+			let aged_out = self.generations[oldest];
+			self.generations[oldest] = spawned;
 			self.t += 1;
+			if lc.lifespan.is_none() {
+				let slot = self.slot(lc.first_spawn(), self.t);
+				self.generations[slot] = self.generations[slot]
+					.checked_add(aged_out)
+					.ok_or_else(|| too_many(self.t))?;
+			}
+		}
+		self.total()
+	}
+
+	// the number of fish at the current time.
+	fn total(&self) -> Result<usize, Box<dyn Error>> {
+		self.generations
+			.iter()
+			.try_fold(0usize, |total, &n| total.checked_add(n))
+			.ok_or_else(|| too_many(self.t))
+	}
+
+	// the number of fish of each age at the current time.
+	fn ages(&self) -> Vec<usize> {
+		(0..self.generations.len())
+			.map(|age| self.generations[self.slot(age, self.t)])
+			.collect()
+	}
+
+	// An endless iterator of the school's state, starting with the current
+	// day. The population soon outgrows a usize, so the series is counted
+	// exactly by applying the daily transition matrix.
+	fn snapshots(&self) -> Snapshots {
+		Snapshots {
+			transition: Matrix::transition(&self.lifecycle, &Exact),
+			t: self.t,
+			ages: self.ages().iter().map(|&n| Exact.num(n as u64)).collect(),
+		}
+	}
+
+	// Computes the population at time t without stepping through each day by
+	// raising the daily transition matrix to the power of the days remaining.
+	fn population_at<A: Arith>(&self, t: u64, a: &A) -> Result<A::T, Box<dyn Error>> {
+		let now = self.t as u64;
+		if t < now {
+			return Err(format!("cannot go back to day {} from day {}", t, now).into());
+		}
+
+		let m = Matrix::transition(&self.lifecycle, a).pow(t - now, a);
+		let ages = self.ages();
+		let mut total = a.zero();
+		for row in &m.cells {
+			for (cell, &n) in row.iter().zip(ages.iter()) {
+				total = a.add(&total, &a.mul(cell, &a.num(n as u64)));
+			}
 		}
-		self.generations.iter().sum()
+		Ok(total)
 	}
 }
 
+fn too_many(t: usize) -> Box<dyn Error> {
+	format!(
+		"the population on day {} is too large to count by stepping through each day, use --day instead",
+		t
+	)
+	.into()
+}
+
+struct Snapshots {
+	transition: Matrix<BigUint>,
+	t: usize,
+	// the number of fish of each age on day t
+	ages: Vec<BigUint>,
+}
+
+impl Iterator for Snapshots {
+	type Item = Snapshot;
+
+	fn next(&mut self) -> Option<Snapshot> {
+		let next = self.transition.apply(&self.ages, &Exact);
+		let snapshot = Snapshot {
+			t: self.t,
+			ages: std::mem::replace(&mut self.ages, next),
+		};
+		self.t += 1;
+		Some(snapshot)
+	}
+}
+
+fn parse_timers(s: &str) -> Result<Vec<usize>, Box<dyn Error>> {
+	s.trim()
+		.split(",")
+		.map(|v| v.parse::<usize>().map_err(|e| e.into()))
+		.collect()
+}
+
 impl FromStr for School {
 	type Err = Box<dyn Error>;
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		let mut generations = [0; 9];
-		for v in s.split(",") {
-			generations[v.parse::<usize>()?] += 1;
+		School::new(Lifecycle::default(), &parse_timers(s)?)
+	}
+}
+
+// The arithmetic used to count fish. Populations grow exponentially, so
+// machine integers only go so far.
+trait Arith {
+	type T: Clone + Display;
+	fn zero(&self) -> Self::T;
+	fn num(&self, v: u64) -> Self::T;
+	fn add(&self, a: &Self::T, b: &Self::T) -> Self::T;
+	fn mul(&self, a: &Self::T, b: &Self::T) -> Self::T;
+}
+
+// exact counts of any size.
+struct Exact;
+
+impl Arith for Exact {
+	type T = BigUint;
+
+	fn zero(&self) -> BigUint {
+		BigUint::from_u64(0)
+	}
+
+	fn num(&self, v: u64) -> BigUint {
+		BigUint::from_u64(v)
+	}
+
+	fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+		a.add(b)
+	}
+
+	fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+		a.mul(b)
+	}
+}
+
+// counts reduced modulo m.
+struct Modular(u64);
+
+impl Arith for Modular {
+	type T = u64;
+
+	fn zero(&self) -> u64 {
+		0
+	}
+
+	fn num(&self, v: u64) -> u64 {
+		v % self.0
+	}
+
+	fn add(&self, a: &u64, b: &u64) -> u64 {
+		((*a as u128 + *b as u128) % self.0 as u128) as u64
+	}
+
+	fn mul(&self, a: &u64, b: &u64) -> u64 {
+		((*a as u128 * *b as u128) % self.0 as u128) as u64
+	}
+}
+
+// approximate counts, only useful for estimating growth.
+struct Float;
+
+impl Arith for Float {
+	type T = f64;
+
+	fn zero(&self) -> f64 {
+		0.0
+	}
+
+	fn num(&self, v: u64) -> f64 {
+		v as f64
+	}
+
+	fn add(&self, a: &f64, b: &f64) -> f64 {
+		a + b
+	}
+
+	fn mul(&self, a: &f64, b: &f64) -> f64 {
+		a * b
+	}
+}
+
+// A square matrix where cells[i][j] is the number of fish of age i that a
+// single fish of age j turns into.
+struct Matrix<T> {
+	cells: Vec<Vec<T>>,
+}
+
+impl<T: Clone> Matrix<T> {
+	fn identity<A: Arith<T = T>>(n: usize, a: &A) -> Matrix<T> {
+		let mut cells = vec![vec![a.zero(); n]; n];
+		for (i, row) in cells.iter_mut().enumerate() {
+			row[i] = a.num(1);
+		}
+		Matrix { cells }
+	}
+
+	// the change over a single day: every fish ages by a day, fish whose
+	// timers pass 0 spawn a newborn and the oldest fish either die or wrap
+	// back around to the age of their first spawn.
+	fn transition<A: Arith<T = T>>(lc: &Lifecycle, a: &A) -> Matrix<T> {
+		let n = lc.ages();
+		let mut cells = vec![vec![a.zero(); n]; n];
+		for age in 0..n {
+			if age + 1 < n {
+				cells[age + 1][age] = a.num(1);
+			} else if lc.lifespan.is_none() {
+				cells[lc.first_spawn()][age] = a.add(&cells[lc.first_spawn()][age], &a.num(1));
+			}
+			if lc.spawns_at(age) {
+				cells[0][age] = a.add(&cells[0][age], &a.num(1));
+			}
+		}
+		Matrix { cells }
+	}
+
+	fn mul<A: Arith<T = T>>(&self, o: &Matrix<T>, a: &A) -> Matrix<T> {
+		let n = self.cells.len();
+		let mut cells = vec![vec![a.zero(); n]; n];
+		for (i, row) in cells.iter_mut().enumerate() {
+			for (j, cell) in row.iter_mut().enumerate() {
+				for k in 0..n {
+					*cell = a.add(cell, &a.mul(&self.cells[i][k], &o.cells[k][j]));
+				}
+			}
 		}
-		Ok(School { generations, t: 0 })
+		Matrix { cells }
+	}
+
+	// the number of fish of each age a day after there were v.
+	fn apply<A: Arith<T = T>>(&self, v: &[T], a: &A) -> Vec<T> {
+		self.cells
+			.iter()
+			.map(|row| {
+				row.iter()
+					.zip(v)
+					.fold(a.zero(), |total, (cell, n)| a.add(&total, &a.mul(cell, n)))
+			})
+			.collect()
+	}
+
+	// exponentiation by squaring.
+	fn pow<A: Arith<T = T>>(&self, mut n: u64, a: &A) -> Matrix<T> {
+		let mut result = Matrix::identity(self.cells.len(), a);
+		let mut base = Matrix {
+			cells: self.cells.clone(),
+		};
+		while n > 0 {
+			if n & 1 == 1 {
+				result = result.mul(&base, a);
+			}
+			n >>= 1;
+			if n > 0 {
+				base = base.mul(&base, a);
+			}
+		}
+		result
 	}
 }
 
+// Estimates the factor by which the population eventually grows each day,
+// which is the dominant eigenvalue of the transition matrix. This uses power
+// iteration on M + I since M alone can be periodic, in which case the daily
+// ratio oscillates rather than converging.
+fn growth_factor(lc: &Lifecycle) -> Option<f64> {
+	let m = Matrix::transition(lc, &Float);
+	let n = m.cells.len();
+	let mut v = vec![1.0 / n as f64; n];
+	let mut last = f64::NAN;
+	for _ in 0..100_000 {
+		let next = m
+			.cells
+			.iter()
+			.zip(v.iter())
+			.map(|(row, x)| x + row.iter().zip(v.iter()).map(|(c, y)| c * y).sum::<f64>())
+			.collect::<Vec<_>>();
+		let r = next.iter().sum::<f64>();
+		v = next.iter().map(|x| x / r).collect();
+		if (r - last).abs() < 1e-12 {
+			return Some(r - 1.0);
+		}
+		last = r;
+	}
+	None
+}
+
+// makes sure the matrix agrees with stepping through each day.
+fn check(lc: Lifecycle, timers: &[usize]) -> Result<(), Box<dyn Error>> {
+	let mut school = School::new(lc, timers)?;
+	for t in [80, 256] {
+		let expected = school.simulate_until(t)?;
+		let actual = School::new(lc, timers)?.population_at(t as u64, &Exact)?;
+		if actual != BigUint::from_u64(expected as u64) {
+			return Err(format!("day {}: expected {}, got {}", t, expected, actual).into());
+		}
+	}
+	Ok(())
+}
+
+// the lifecycle settings that can be overridden on the command line.
+const LIFECYCLE_SETTINGS: &[(&str, &str)] = &[
+	("initial", "the timer of a newborn fish"),
+	("reset", "the timer of a fish after it spawns"),
+	(
+		"maturation",
+		"the days a newborn waits before its timer starts",
+	),
+	("lifespan", "the age in days at which fish die"),
+];
+
 fn main() -> Result<(), Box<dyn Error>> {
 	let matches = clap::App::new("day06")
 		.arg(
@@ -40,11 +454,135 @@ fn main() -> Result<(), Box<dyn Error>> {
 				.takes_value(true)
 				.help("the input file"),
 		)
+		.arg(
+			clap::Arg::with_name("day")
+				.long("day")
+				.takes_value(true)
+				.help("also report the population on this day"),
+		)
+		.arg(
+			clap::Arg::with_name("modulus")
+				.long("modulus")
+				.takes_value(true)
+				.help("report the population on --day modulo this number"),
+		)
+		.arg(
+			clap::Arg::with_name("check")
+				.long("check")
+				.help("cross-check the matrix against the daily simulation"),
+		)
+		.arg(
+			clap::Arg::with_name("config")
+				.long("config")
+				.takes_value(true)
+				.help("a file of name = value settings for the lifecycle"),
+		)
+		.args(
+			&LIFECYCLE_SETTINGS
+				.iter()
+				.map(|&(name, help)| {
+					clap::Arg::with_name(name)
+						.long(name)
+						.takes_value(true)
+						.help(help)
+				})
+				.collect::<Vec<_>>(),
+		)
+		.arg(
+			clap::Arg::with_name("from")
+				.long("from")
+				.takes_value(true)
+				.requires("to")
+				.help("the first day of the population series to export"),
+		)
+		.arg(
+			clap::Arg::with_name("to")
+				.long("to")
+				.takes_value(true)
+				.help("the last day of the population series to export"),
+		)
+		.arg(
+			clap::Arg::with_name("format")
+				.long("format")
+				.takes_value(true)
+				.possible_values(&["csv", "json"])
+				.default_value("csv")
+				.help("the format of the population series"),
+		)
+		.arg(
+			clap::Arg::with_name("output")
+				.long("output")
+				.takes_value(true)
+				.help("where to write the population series instead of stdout"),
+		)
+		.arg(
+			clap::Arg::with_name("growth")
+				.long("growth")
+				.help("report the factor the population eventually grows by each day"),
+		)
 		.get_matches();
 
-	let mut school = read_to_string(matches.value_of("input").unwrap_or("data/day06/input.txt"))?
-		.parse::<School>()?;
-	println!("Part 1: {}", school.simulate_until(80));
-	println!("Part 2: {}", school.simulate_until(256));
+	let mut lifecycle = match matches.value_of("config") {
+		Some(path) => Lifecycle::from_config(&read_to_string(path)?)?,
+		None => Lifecycle::default(),
+	};
+	for &(name, _) in LIFECYCLE_SETTINGS {
+		if let Some(value) = matches.value_of(name) {
+			lifecycle.set(name, value)?;
+		}
+	}
+
+	let timers = parse_timers(&read_to_string(
+		matches.value_of("input").unwrap_or("data/day06/input.txt"),
+	)?)?;
+	let mut school = School::new(lifecycle, &timers)?;
+	println!("Part 1: {}", school.simulate_until(80)?);
+	println!("Part 2: {}", school.simulate_until(256)?);
+
+	if let Some(t) = matches.value_of("day") {
+		let t = t.parse::<u64>()?;
+		let school = School::new(lifecycle, &timers)?;
+		match matches.value_of("modulus") {
+			Some(m) => match m.parse::<u64>()? {
+				0 => return Err("modulus must be positive".into()),
+				m => println!("Day {}: {}", t, school.population_at(t, &Modular(m))?),
+			},
+			None => println!("Day {}: {}", t, school.population_at(t, &Exact)?),
+		}
+	}
+
+	if matches.is_present("growth") {
+		match growth_factor(&lifecycle) {
+			Some(r) => println!("Growth factor: {:.6} per day", r),
+			None => println!("Growth factor: did not converge"),
+		}
+	}
+
+	if let Some(to) = matches.value_of("to") {
+		let to = to.parse::<usize>()?;
+		let from = matches.value_of("from").unwrap_or("0").parse::<usize>()?;
+		let snapshots = School::new(lifecycle, &timers)?
+			.snapshots()
+			.skip(from)
+			.take((to + 1).saturating_sub(from))
+			.collect::<Vec<_>>();
+		let format = matches
+			.value_of("format")
+			.unwrap()
+			.parse::<series::Format>()?;
+		match matches.value_of("output") {
+			Some(path) => {
+				let mut w = BufWriter::new(File::create(path)?);
+				series::write(&mut w, format, &snapshots)?;
+				w.flush()?;
+			}
+			None => series::write(&mut io::stdout().lock(), format, &snapshots)?,
+		}
+	}
+
+	if matches.is_present("check") {
+		check(lifecycle, &timers)?;
+	}
+
 	Ok(())
 }
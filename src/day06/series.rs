@@ -0,0 +1,85 @@
+use super::bignum::BigUint;
+use std::io::{self, Write};
+
+// The state of a school at the end of a single day.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+	pub t: usize,
+	// the number of fish of each age
+	pub ages: Vec<BigUint>,
+}
+
+impl Snapshot {
+	pub fn total(&self) -> BigUint {
+		self.ages
+			.iter()
+			.fold(BigUint::from_u64(0), |total, n| total.add(n))
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Csv,
+	Json,
+}
+
+impl std::str::FromStr for Format {
+	type Err = Box<dyn std::error::Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"csv" => Ok(Format::Csv),
+			"json" => Ok(Format::Json),
+			_ => Err(format!("invalid format: {}", s).into()),
+		}
+	}
+}
+
+// Writes one row per day with the total followed by the count for each age.
+fn write_csv<W: Write>(w: &mut W, snapshots: &[Snapshot]) -> io::Result<()> {
+	let n = snapshots.first().map_or(0, |s| s.ages.len());
+	write!(w, "day,total")?;
+	for age in 0..n {
+		write!(w, ",age{}", age)?;
+	}
+	writeln!(w)?;
+
+	for s in snapshots {
+		write!(w, "{},{}", s.t, s.total())?;
+		for n in &s.ages {
+			write!(w, ",{}", n)?;
+		}
+		writeln!(w)?;
+	}
+	Ok(())
+}
+
+// Writes an array with an object per day.
+fn write_json<W: Write>(w: &mut W, snapshots: &[Snapshot]) -> io::Result<()> {
+	writeln!(w, "[")?;
+	for (i, s) in snapshots.iter().enumerate() {
+		let ages = s
+			.ages
+			.iter()
+			.map(|n| n.to_string())
+			.collect::<Vec<_>>()
+			.join(",");
+		let sep = if i + 1 < snapshots.len() { "," } else { "" };
+		writeln!(
+			w,
+			"  {{\"day\":{},\"total\":{},\"ages\":[{}]}}{}",
+			s.t,
+			s.total(),
+			ages,
+			sep
+		)?;
+	}
+	writeln!(w, "]")
+}
+
+pub fn write<W: Write>(w: &mut W, format: Format, snapshots: &[Snapshot]) -> io::Result<()> {
+	match format {
+		Format::Csv => write_csv(w, snapshots),
+		Format::Json => write_json(w, snapshots),
+	}
+}
@@ -0,0 +1,329 @@
+use std::error::Error;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+// A fuel cost expression over the distance d a crab moves. Expressions
+// support integer literals, d, the operators + - * / % and ^ (power), unary
+// minus, parentheses and the functions min, max and abs.
+//
+//   d
+//   d*(d+1)/2
+//   min(d,10)*3
+#[derive(Debug, Clone)]
+pub enum Expr {
+	Num(isize),
+	Dist,
+	Neg(Box<Expr>),
+	Binary(Op, Box<Expr>, Box<Expr>),
+	Call(Func, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Rem,
+	Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Func {
+	Min,
+	Max,
+	Abs,
+}
+
+impl Func {
+	fn from_name(name: &str) -> Option<Func> {
+		match name {
+			"min" => Some(Func::Min),
+			"max" => Some(Func::Max),
+			"abs" => Some(Func::Abs),
+			_ => None,
+		}
+	}
+
+	fn arity(self) -> usize {
+		match self {
+			Func::Min | Func::Max => 2,
+			Func::Abs => 1,
+		}
+	}
+}
+
+fn overflow() -> Box<dyn Error> {
+	"arithmetic overflow".into()
+}
+
+impl Expr {
+	// Evaluates the expression for a distance. Every operation is checked, so
+	// overflow and division by zero are reported rather than panicking.
+	pub fn eval(&self, d: isize) -> Result<isize, Box<dyn Error>> {
+		match self {
+			Expr::Num(n) => Ok(*n),
+			Expr::Dist => Ok(d),
+			Expr::Neg(e) => e.eval(d)?.checked_neg().ok_or_else(overflow),
+			Expr::Binary(op, a, b) => {
+				let (a, b) = (a.eval(d)?, b.eval(d)?);
+				match op {
+					Op::Add => a.checked_add(b).ok_or_else(overflow),
+					Op::Sub => a.checked_sub(b).ok_or_else(overflow),
+					Op::Mul => a.checked_mul(b).ok_or_else(overflow),
+					Op::Div if b == 0 => Err("division by zero".into()),
+					Op::Div => a.checked_div(b).ok_or_else(overflow),
+					Op::Rem if b == 0 => Err("division by zero".into()),
+					Op::Rem => a.checked_rem(b).ok_or_else(overflow),
+					Op::Pow => match u32::try_from(b) {
+						Ok(b) => a.checked_pow(b).ok_or_else(overflow),
+						Err(_) => Err(format!("invalid exponent: {}", b).into()),
+					},
+				}
+			}
+			Expr::Call(f, args) => {
+				let args = args
+					.iter()
+					.map(|e| e.eval(d))
+					.collect::<Result<Vec<_>, _>>()?;
+				match f {
+					Func::Min => Ok(args[0].min(args[1])),
+					Func::Max => Ok(args[0].max(args[1])),
+					Func::Abs => args[0].checked_abs().ok_or_else(overflow),
+				}
+			}
+		}
+	}
+}
+
+impl FromStr for Expr {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut p = Parser {
+			src: s,
+			chars: s.char_indices().peekable(),
+			depth: 0,
+		};
+		let e = p.expr()?;
+		p.skip_whitespace();
+		match p.chars.peek() {
+			None => Ok(e),
+			Some(&(i, c)) => Err(format!("unexpected '{}' at {}", c, i).into()),
+		}
+	}
+}
+
+// the deepest an expression may nest, which keeps parsing, evaluating and
+// dropping it from overflowing the stack.
+const MAX_DEPTH: usize = 256;
+
+// A recursive descent parser for the grammar:
+//
+//   expr  = term (('+' | '-') term)*
+//   term  = unary (('*' | '/' | '%') unary)*
+//   unary = '-' unary | power
+//   power = atom ('^' unary)?
+//   atom  = number | 'd' | name '(' expr (',' expr)* ')' | '(' expr ')'
+//
+// so ^ binds tighter than unary minus, and -d^2 is -(d^2).
+struct Parser<'a> {
+	src: &'a str,
+	chars: Peekable<CharIndices<'a>>,
+	// how deep the expression nests so far, counting each operator in a run
+	// of + - * / % as a level too since they nest in the tree
+	depth: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn skip_whitespace(&mut self) {
+		while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+	}
+
+	// consumes the next non-whitespace character if it is c.
+	fn eat(&mut self, c: char) -> bool {
+		self.skip_whitespace();
+		self.chars.next_if(|&(_, n)| n == c).is_some()
+	}
+
+	fn expect(&mut self, c: char) -> Result<(), Box<dyn Error>> {
+		if self.eat(c) {
+			return Ok(());
+		}
+		match self.chars.peek() {
+			Some(&(i, n)) => Err(format!("expected '{}' at {}, found '{}'", c, i, n).into()),
+			None => Err(format!("expected '{}' at end of expression", c).into()),
+		}
+	}
+
+	// goes one level deeper into the expression.
+	fn nest(&mut self) -> Result<(), Box<dyn Error>> {
+		self.depth += 1;
+		if self.depth > MAX_DEPTH {
+			return Err(format!("expression nests more than {} deep", MAX_DEPTH).into());
+		}
+		Ok(())
+	}
+
+	fn expr(&mut self) -> Result<Expr, Box<dyn Error>> {
+		let depth = self.depth;
+		let mut e = self.term()?;
+		loop {
+			let op = if self.eat('+') {
+				Op::Add
+			} else if self.eat('-') {
+				Op::Sub
+			} else {
+				self.depth = depth;
+				return Ok(e);
+			};
+			self.nest()?;
+			e = Expr::Binary(op, Box::new(e), Box::new(self.term()?));
+		}
+	}
+
+	fn term(&mut self) -> Result<Expr, Box<dyn Error>> {
+		let depth = self.depth;
+		let mut e = self.unary()?;
+		loop {
+			let op = if self.eat('*') {
+				Op::Mul
+			} else if self.eat('/') {
+				Op::Div
+			} else if self.eat('%') {
+				Op::Rem
+			} else {
+				self.depth = depth;
+				return Ok(e);
+			};
+			self.nest()?;
+			e = Expr::Binary(op, Box::new(e), Box::new(self.unary()?));
+		}
+	}
+
+	fn unary(&mut self) -> Result<Expr, Box<dyn Error>> {
+		self.nest()?;
+		let e = if self.eat('-') {
+			Expr::Neg(Box::new(self.unary()?))
+		} else {
+			self.power()?
+		};
+		self.depth -= 1;
+		Ok(e)
+	}
+
+	fn power(&mut self) -> Result<Expr, Box<dyn Error>> {
+		let e = self.atom()?;
+		if self.eat('^') {
+			Ok(Expr::Binary(Op::Pow, Box::new(e), Box::new(self.unary()?)))
+		} else {
+			Ok(e)
+		}
+	}
+
+	// consumes a run of characters matching f and returns it.
+	fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+		let start = self.chars.peek().map_or(self.src.len(), |&(i, _)| i);
+		while self.chars.next_if(|&(_, c)| f(c)).is_some() {}
+		let end = self.chars.peek().map_or(self.src.len(), |&(i, _)| i);
+		&self.src[start..end]
+	}
+
+	fn atom(&mut self) -> Result<Expr, Box<dyn Error>> {
+		self.skip_whitespace();
+		let (i, c) = match self.chars.peek() {
+			Some(&p) => p,
+			None => return Err("unexpected end of expression".into()),
+		};
+
+		if c == '(' {
+			self.chars.next();
+			let e = self.expr()?;
+			self.expect(')')?;
+			Ok(e)
+		} else if c.is_ascii_digit() {
+			Ok(Expr::Num(self.take_while(|c| c.is_ascii_digit()).parse()?))
+		} else if c.is_ascii_alphabetic() {
+			let name = self.take_while(|c| c.is_ascii_alphanumeric());
+			if name == "d" {
+				return Ok(Expr::Dist);
+			}
+			let f = match Func::from_name(name) {
+				Some(f) => f,
+				None => return Err(format!("unknown name '{}' at {}", name, i).into()),
+			};
+			self.expect('(')?;
+			let mut args = vec![self.expr()?];
+			while self.eat(',') {
+				args.push(self.expr()?);
+			}
+			self.expect(')')?;
+			if args.len() != f.arity() {
+				return Err(format!(
+					"{} takes {} arguments, found {}",
+					name,
+					f.arity(),
+					args.len()
+				)
+				.into());
+			}
+			Ok(Expr::Call(f, args))
+		} else {
+			Err(format!("unexpected '{}' at {}", c, i).into())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn eval(s: &str, d: isize) -> isize {
+		s.parse::<Expr>().unwrap().eval(d).unwrap()
+	}
+
+	#[test]
+	fn precedence() {
+		assert_eq!(eval("-d^2", 3), -9);
+		assert_eq!(eval("(-d)^2", 3), 9);
+		assert_eq!(eval("--d", 3), 3);
+		assert_eq!(eval("2*-d", 3), -6);
+		assert_eq!(eval("d^2^2", 3), 81);
+		assert_eq!(eval("2*d^2", 3), 18);
+		assert_eq!(eval("1+2*3", 0), 7);
+		assert_eq!(eval("10-4-3", 0), 3);
+		assert_eq!(eval("24/4/2", 0), 3);
+		assert_eq!(eval("7%4*2", 0), 6);
+		assert_eq!(eval("d*(d+1)/2", 4), 10);
+		assert_eq!(eval(" min( d , 10 ) * 3 ", 12), 30);
+		assert_eq!(eval("max(d,10)+abs(-d)", 3), 13);
+	}
+
+	#[test]
+	fn parse_errors() {
+		for s in [
+			"", "d+", "(d", "d)", "2 3", "foo(d)", "min(d)", "abs(d,1)", "d^", "#",
+		] {
+			assert!(s.parse::<Expr>().is_err(), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn eval_errors() {
+		for s in ["d/0", "d%0", "d^-1", "d^99", "-(2^62)*2-2^62*2"] {
+			assert!(s.parse::<Expr>().unwrap().eval(2).is_err(), "{:?}", s);
+		}
+	}
+
+	#[test]
+	fn nesting_is_limited() {
+		let deep =
+			|open: &str, close: &str, n: usize| format!("{}d{}", open.repeat(n), close.repeat(n));
+		assert!(deep("(", ")", MAX_DEPTH / 2).parse::<Expr>().is_ok());
+		assert!(deep("(", ")", 100_000).parse::<Expr>().is_err());
+		assert!(deep("-", "", 100_000).parse::<Expr>().is_err());
+		assert!(deep("d^", "", 100_000).parse::<Expr>().is_err());
+		assert!(deep("", "+d", 100_000).parse::<Expr>().is_err());
+		assert!(deep("min(d,", ")", 100_000).parse::<Expr>().is_err());
+	}
+}
@@ -1,15 +1,27 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs::read_to_string;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
-fn total_cost<F>(positions: &[isize], x: isize, f: F) -> isize
+mod expr;
+mod plan;
+
+// the fuel for every crab to move to x. Like Expr::eval, overflow is reported
+// rather than panicking.
+fn total_cost<F>(positions: &[isize], x: isize, f: F) -> Result<isize, Box<dyn Error>>
 where
 	F: Fn(isize, isize) -> isize,
 {
-	positions.iter().map(|&p| f(p, x)).sum::<isize>()
+	positions
+		.iter()
+		.try_fold(0_isize, |total, &p| total.checked_add(f(p, x)))
+		.ok_or_else(|| format!("total cost at x = {}: arithmetic overflow", x).into())
 }
 
 // the total cost is a convex function so this is a crappy gradient descent.
-fn find_min<F>(positions: &[isize], cost: F) -> isize
+fn find_min<F>(positions: &[isize], cost: F) -> Result<isize, Box<dyn Error>>
 where
 	F: Fn(isize, isize) -> isize,
 {
@@ -17,9 +29,11 @@ where
 	let mut max = *positions.iter().max().unwrap();
 
 	while min < max {
-		let mp = (min + max) / 2;
-		let ca = total_cost(positions, mp, &cost);
-		let cb = total_cost(positions, mp + 1, &cost);
+		// rounding down rather than towards 0 keeps mp below max when the
+		// positions are negative
+		let mp = (min + max).div_euclid(2);
+		let ca = total_cost(positions, mp, &cost)?;
+		let cb = total_cost(positions, mp + 1, &cost)?;
 		if ca > cb {
 			min = mp + 1
 		} else {
@@ -27,7 +41,341 @@ where
 		}
 	}
 
-	total_cost(&positions, min, &cost)
+	total_cost(positions, min, &cost)
+}
+
+// checks every target between the outermost crabs, which works for any cost.
+fn find_min_by_scan<F>(positions: &[isize], cost: F) -> Result<isize, Box<dyn Error>>
+where
+	F: Fn(isize, isize) -> isize,
+{
+	let min = *positions.iter().min().unwrap();
+	let max = *positions.iter().max().unwrap();
+	let mut best = isize::MAX;
+	for x in min..=max {
+		best = best.min(total_cost(positions, x, &cost)?);
+	}
+	Ok(best)
+}
+
+// The fuel for each distance a crab moves by a cost expression. Each
+// distance is evaluated when it's first needed and remembered after that, so
+// only the distances that are actually used are kept.
+struct Costs<'a> {
+	e: &'a expr::Expr,
+	known: RefCell<HashMap<usize, isize>>,
+}
+
+impl<'a> Costs<'a> {
+	fn new(e: &'a expr::Expr) -> Costs<'a> {
+		Costs {
+			e,
+			known: RefCell::new(HashMap::new()),
+		}
+	}
+
+	fn eval(&self, d: usize) -> Result<isize, Box<dyn Error>> {
+		isize::try_from(d)
+			.map_err(|err| err.into())
+			.and_then(|d| self.e.eval(d))
+			.map_err(|err| format!("cost at d = {}: {}", d, err).into())
+	}
+
+	fn get(&self, d: usize) -> Result<isize, Box<dyn Error>> {
+		if let Some(&fuel) = self.known.borrow().get(&d) {
+			return Ok(fuel);
+		}
+		let fuel = self.eval(d)?;
+		self.known.borrow_mut().insert(d, fuel);
+		Ok(fuel)
+	}
+
+	// The cost of moving a crab to x is f(|p - x|), which is convex in x as
+	// long as f never decreases moving away from 0 and has no negative second
+	// differences. The total cost is then a sum of convex functions. This
+	// evaluates every distance up to max, so a bad expression fails before
+	// any searching happens, but only keeps the last two.
+	fn is_convex(&self, max: usize) -> Result<bool, Box<dyn Error>> {
+		let (mut a, mut b) = (None, None);
+		let mut convex = true;
+		for d in 0..=max {
+			let c = self.eval(d)?;
+			convex &= match (a, b) {
+				(None, Some(b)) => c >= b,
+				(Some(a), Some(b)) => c - b >= b - a,
+				_ => true,
+			};
+			(a, b) = (b, Some(c));
+		}
+		Ok(convex)
+	}
+}
+
+// Finds the cheapest target for a cost expression. Bisection is only correct
+// when the total cost is convex, so other costs fall back to checking every
+// target.
+fn find_min_by_expr(positions: &[isize], e: &expr::Expr) -> Result<isize, Box<dyn Error>> {
+	let min = *positions.iter().min().unwrap();
+	let max = *positions.iter().max().unwrap();
+	let costs = Costs::new(e);
+	let convex = costs.is_convex((max - min).unsigned_abs())?;
+	let cost = |a: isize, b: isize| {
+		costs
+			.get((a - b).unsigned_abs())
+			.expect("every distance was evaluated by is_convex")
+	};
+	if convex {
+		find_min(positions, cost)
+	} else {
+		find_min_by_scan(positions, cost)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cost {
+	// each step costs 1 fuel
+	Linear,
+	// each step costs 1 more fuel than the last
+	Triangular,
+}
+
+impl Cost {
+	fn of(self, a: isize, b: isize) -> isize {
+		let n = (a - b).abs();
+		match self {
+			Cost::Linear => n,
+			Cost::Triangular => (n * (n + 1)) / 2,
+		}
+	}
+}
+
+// Evaluates the total cost of moving every crab to a target in O(log n) by
+// keeping prefix sums over the sorted positions. Both costs are polynomials
+// in the distance, so the sum of distances only needs to be split at the
+// target and the sum of squared distances expands to Σp² - 2xΣp + nx². Like
+// total_cost, overflow is reported rather than panicking.
+struct PrefixSums {
+	sorted: Vec<isize>,
+	// sums[i] is the sum of the first i sorted positions
+	sums: Vec<isize>,
+	sum_of_squares: isize,
+}
+
+impl PrefixSums {
+	fn new(positions: &[isize]) -> Result<PrefixSums, Box<dyn Error>> {
+		let mut sorted = positions.to_vec();
+		sorted.sort_unstable();
+		let mut sums = Vec::with_capacity(sorted.len() + 1);
+		sums.push(0_isize);
+		let mut sum_of_squares = 0_isize;
+		for &p in &sorted {
+			let sum = sums
+				.last()
+				.unwrap()
+				.checked_add(p)
+				.ok_or("sum of positions: arithmetic overflow")?;
+			sums.push(sum);
+			sum_of_squares = p
+				.checked_mul(p)
+				.and_then(|sq| sum_of_squares.checked_add(sq))
+				.ok_or("sum of squared positions: arithmetic overflow")?;
+		}
+		Ok(PrefixSums {
+			sorted,
+			sums,
+			sum_of_squares,
+		})
+	}
+
+	fn sum_of_distances(&self, x: isize) -> Option<isize> {
+		let n = self.sorted.len();
+		let k = self.sorted.partition_point(|&p| p < x);
+		let below = x.checked_mul(k as isize)?.checked_sub(self.sums[k])?;
+		let above = (self.sums[n].checked_sub(self.sums[k])?)
+			.checked_sub(x.checked_mul((n - k) as isize)?)?;
+		below.checked_add(above)
+	}
+
+	fn sum_of_squared_distances(&self, x: isize) -> Option<isize> {
+		let n = self.sorted.len() as isize;
+		self.sum_of_squares
+			.checked_sub(
+				x.checked_mul(self.sums[self.sorted.len()])?
+					.checked_mul(2)?,
+			)?
+			.checked_add(n.checked_mul(x)?.checked_mul(x)?)
+	}
+
+	fn total_cost(&self, x: isize, cost: Cost) -> Result<isize, Box<dyn Error>> {
+		match cost {
+			Cost::Linear => self.sum_of_distances(x),
+			Cost::Triangular => self
+				.sum_of_squared_distances(x)
+				.and_then(|sq| sq.checked_add(self.sum_of_distances(x)?))
+				.map(|total| total / 2),
+		}
+		.ok_or_else(|| format!("total cost at x = {}: arithmetic overflow", x).into())
+	}
+
+	// The cheapest total cost, found in O(n log n) by only evaluating the
+	// targets where it can be. Between crabs the linear cost is a line, so its
+	// minimum is at a crab. The triangular cost's minimum is near the mean.
+	fn find_min(&self, cost: Cost) -> Result<isize, Box<dyn Error>> {
+		let n = self.sorted.len();
+		let near_mean = near_mean(self.sums[n], n).filter(|_| cost == Cost::Triangular);
+		let mut best = isize::MAX;
+		for x in self.sorted.iter().copied().chain(near_mean) {
+			best = best.min(self.total_cost(x, cost)?);
+		}
+		Ok(best)
+	}
+}
+
+// the linear cost is minimized at the median.
+fn find_min_at_median(positions: &[isize]) -> Result<isize, Box<dyn Error>> {
+	let mut sorted = positions.to_vec();
+	sorted.sort_unstable();
+	total_cost(positions, sorted[sorted.len() / 2], |a, b| {
+		Cost::Linear.of(a, b)
+	})
+}
+
+// The targets where the triangular cost can be cheapest for n crabs whose
+// positions add up to sum. The cost is (d² + d)/2, so the minimum is within
+// 1/2 of the mean, which puts the cheapest integer target at most 1 away
+// from the mean rounded down.
+fn near_mean(sum: isize, n: usize) -> RangeInclusive<isize> {
+	let mean = sum.div_euclid(n as isize);
+	mean.saturating_sub(1)..=mean.saturating_add(1)
+}
+
+// checks the integers around the mean, which is enough to find the minimum
+// of the triangular cost.
+fn find_min_near_mean(positions: &[isize]) -> Result<isize, Box<dyn Error>> {
+	let sum = positions
+		.iter()
+		.try_fold(0_isize, |sum, &p| sum.checked_add(p))
+		.ok_or("sum of positions: arithmetic overflow")?;
+	let mut best = isize::MAX;
+	for x in near_mean(sum, positions.len()) {
+		best = best.min(total_cost(positions, x, |a, b| Cost::Triangular.of(a, b))?);
+	}
+	Ok(best)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Solver {
+	// bisect over the targets, relying on the total cost being convex
+	Bisect,
+	// go straight to the median or the mean
+	Exact,
+	// evaluate only the targets the minimum can be at using prefix sums
+	Prefix,
+}
+
+impl FromStr for Solver {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"bisect" => Ok(Solver::Bisect),
+			"exact" => Ok(Solver::Exact),
+			"prefix" => Ok(Solver::Prefix),
+			_ => Err(format!("invalid solver: {}", s).into()),
+		}
+	}
+}
+
+fn solve(
+	positions: &[isize],
+	cost: Cost,
+	solver: Solver,
+	check: bool,
+) -> Result<isize, Box<dyn Error>> {
+	let fuel = match (solver, cost) {
+		(Solver::Bisect, _) => find_min(positions, |a, b| cost.of(a, b))?,
+		(Solver::Exact, Cost::Linear) => find_min_at_median(positions)?,
+		(Solver::Exact, Cost::Triangular) => find_min_near_mean(positions)?,
+		(Solver::Prefix, _) => PrefixSums::new(positions)?.find_min(cost)?,
+	};
+
+	if check {
+		let expected = find_min(positions, |a, b| cost.of(a, b))?;
+		if fuel != expected {
+			return Err(format!(
+				"{:?} found {} fuel for {:?} cost, expected {}",
+				solver, fuel, cost, expected
+			)
+			.into());
+		}
+	}
+
+	Ok(fuel)
+}
+
+fn parse_list(s: &str) -> Result<Vec<isize>, Box<dyn Error>> {
+	s.trim()
+		.split(",")
+		.map(|s| s.trim().parse::<isize>().map_err(|e| e.into()))
+		.collect()
+}
+
+fn report_plan(
+	matches: &clap::ArgMatches,
+	positions: &[isize],
+	cost: &str,
+) -> Result<(), Box<dyn Error>> {
+	let weights = match matches.value_of("weights") {
+		Some(path) => parse_list(&read_to_string(path)?)?,
+		None => vec![1; positions.len()],
+	};
+	if weights.len() != positions.len() {
+		return Err(format!(
+			"found {} weights for {} crabs",
+			weights.len(),
+			positions.len()
+		)
+		.into());
+	}
+	let crabs = positions
+		.iter()
+		.zip(weights.iter())
+		.map(|(&pos, &weight)| plan::Crab { pos, weight })
+		.collect::<Vec<_>>();
+
+	let allowed = match matches.value_of("allowed") {
+		Some(list) => parse_list(list)?,
+		None => {
+			let min = *positions.iter().min().unwrap();
+			let max = *positions.iter().max().unwrap();
+			(min..=max).collect()
+		}
+	};
+	let k = matches
+		.value_of("meeting-points")
+		.unwrap_or("1")
+		.parse::<usize>()?;
+
+	let e = cost.parse::<expr::Expr>()?;
+	let costs = Costs::new(&e);
+	let plan = plan::plan(&crabs, &allowed, k, |d| costs.get(d))?;
+
+	println!("Plan: {} fuel", plan.fuel);
+	for &t in &plan.targets {
+		let (n, fuel) = plan
+			.moves
+			.iter()
+			.filter(|&&(target, _)| target == t)
+			.fold((0, 0), |(n, total), &(_, fuel)| (n + 1, total + fuel));
+		println!("Target {}: {} crabs, {} fuel", t, n, fuel);
+	}
+	for (i, (c, (t, fuel))) in crabs.iter().zip(plan.moves.iter()).enumerate() {
+		println!(
+			"Crab {}: {} -> {}, weight {}, {} fuel",
+			i, c.pos, t, c.weight, fuel
+		);
+	}
+	Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -37,19 +385,144 @@ fn main() -> Result<(), Box<dyn Error>> {
 				.takes_value(true)
 				.help("the input file"),
 		)
+		.arg(
+			clap::Arg::with_name("solver")
+				.long("solver")
+				.takes_value(true)
+				.possible_values(&["bisect", "exact", "prefix"])
+				.default_value("bisect")
+				.help("how the cheapest target is found"),
+		)
+		.arg(
+			clap::Arg::with_name("check")
+				.long("check")
+				.help("cross-check the result against bisection"),
+		)
+		.arg(
+			clap::Arg::with_name("cost")
+				.long("cost")
+				.takes_value(true)
+				.allow_hyphen_values(true)
+				.help(
+				"also report the fuel for a cost expression over the distance d, e.g. d*(d+1)/2",
+			),
+		)
+		.arg(
+			clap::Arg::with_name("plan")
+				.long("plan")
+				.takes_value(true)
+				.allow_hyphen_values(true)
+				.help("report where each crab moves for a cost expression over the distance d"),
+		)
+		.arg(
+			clap::Arg::with_name("weights")
+				.long("weights")
+				.takes_value(true)
+				.requires("plan")
+				.help("a file of comma separated weights, one for each crab"),
+		)
+		.arg(
+			clap::Arg::with_name("allowed")
+				.long("allowed")
+				.takes_value(true)
+				.requires("plan")
+				.help("the comma separated positions crabs may meet at"),
+		)
+		.arg(
+			clap::Arg::with_name("meeting-points")
+				.long("meeting-points")
+				.takes_value(true)
+				.requires("plan")
+				.help("the number of places crabs may meet at"),
+		)
 		.get_matches();
 
-	let positions = read_to_string(matches.value_of("input").unwrap_or("data/day07/input.txt"))?
-		.split(",")
-		.map(|s| s.parse::<isize>())
-		.collect::<Result<Vec<_>, _>>()?;
-	println!("Part 1: {}", find_min(&positions, |a, b| (a - b).abs()));
-	println!(
-		"Part 2: {}",
-		find_min(&positions, |a, b| {
-			let n = (a - b).abs();
-			(n * (n + 1)) / 2
-		})
+	let solver = matches.value_of("solver").unwrap().parse::<Solver>()?;
+	let check = matches.is_present("check");
+
+	let positions = parse_list(&read_to_string(
+		matches.value_of("input").unwrap_or("data/day07/input.txt"),
+	)?)?;
+	// the parts only share the positions, so they're solved at the same time
+	let (part1, part2) = aoc::parts::both(
+		|| solve(&positions, Cost::Linear, solver, check).map_err(|e| e.to_string()),
+		|| solve(&positions, Cost::Triangular, solver, check).map_err(|e| e.to_string()),
 	);
+	println!("Part 1: {}", part1?);
+	println!("Part 2: {}", part2?);
+
+	if let Some(cost) = matches.value_of("cost") {
+		let e = cost.parse::<expr::Expr>()?;
+		println!("Cost {}: {}", cost, find_min_by_expr(&positions, &e)?);
+	}
+
+	if let Some(cost) = matches.value_of("plan") {
+		report_plan(&matches, &positions, cost)?;
+	}
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn find_min_with_negative_positions() {
+		for positions in [
+			vec![-3, -2],
+			vec![-16, -1, -2, 0, -4, -2, -7, -1, -2, -14],
+			vec![-5, 3, 8],
+		] {
+			for cost in [Cost::Linear, Cost::Triangular] {
+				let expected = find_min_by_scan(&positions, |a, b| cost.of(a, b)).unwrap();
+				assert_eq!(
+					find_min(&positions, |a, b| cost.of(a, b)).unwrap(),
+					expected,
+					"{:?} {:?}",
+					positions,
+					cost
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn solvers_agree() {
+		let mut rng = aoc::rng::Rng::new(7);
+		for n in 1..50 {
+			let positions = (0..n).map(|_| rng.between(-40, 40)).collect::<Vec<_>>();
+			for cost in [Cost::Linear, Cost::Triangular] {
+				let expected = find_min_by_scan(&positions, |a, b| cost.of(a, b)).unwrap();
+				for solver in [Solver::Bisect, Solver::Exact, Solver::Prefix] {
+					assert_eq!(
+						solve(&positions, cost, solver, false).unwrap(),
+						expected,
+						"{:?} {:?} {:?}",
+						positions,
+						cost,
+						solver
+					);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn prefix_sums_report_overflow() {
+		let positions = [isize::MAX / 2, isize::MAX / 2, isize::MAX / 2];
+		assert!(PrefixSums::new(&positions).is_err());
+		let sums = PrefixSums::new(&[1 << 30, -(1 << 30)]).unwrap();
+		assert!(sums.total_cost(isize::MAX / 2, Cost::Triangular).is_err());
+	}
+
+	#[test]
+	fn cost_expressions() {
+		let positions = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+		let min = |cost: &str| find_min_by_expr(&positions, &cost.parse().unwrap()).unwrap();
+		assert_eq!(min("d"), 37);
+		assert_eq!(min("d*(d+1)/2"), 168);
+		// not convex, so every target is checked
+		assert_eq!(min("d%3"), 5);
+		assert!(find_min_by_expr(&positions, &"1/(d-3)".parse().unwrap()).is_err());
+	}
+}
@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Crab {
+	pub pos: isize,
+	pub weight: isize,
+}
+
+// Where each crab ends up and how much fuel it takes to get there.
+#[derive(Debug)]
+pub struct Plan {
+	pub targets: Vec<isize>,
+	pub fuel: isize,
+	// the target and the fuel for each crab, in the same order as the crabs
+	pub moves: Vec<(isize, isize)>,
+}
+
+// Finds the cheapest way to move every crab to one of at most k meeting
+// points chosen from the allowed targets, where moving a crab a distance d
+// costs its weight times fuel(d).
+//
+// As long as fuel never decreases with distance, each crab goes to its
+// nearest meeting point, so the crabs sent to each meeting point are a
+// contiguous run of positions. This partitions the sorted positions into k
+// runs with dynamic programming, in the style of 1-d k-median. Fuel is only
+// asked for the distances from a crab to an allowed target, and fuel that
+// decreases between any two of them is rejected since the runs could then
+// be wrong.
+//
+// With n distinct positions this takes O(n²·|allowed|) time to find the
+// best target for every run, plus O(k·n²) for the partitioning, so it's
+// meant for a modest number of crabs.
+pub fn plan<F>(crabs: &[Crab], allowed: &[isize], k: usize, fuel: F) -> Result<Plan, Box<dyn Error>>
+where
+	F: Fn(usize) -> Result<isize, Box<dyn Error>>,
+{
+	if crabs.is_empty() {
+		return Err("no crabs".into());
+	}
+	if allowed.is_empty() {
+		return Err("no allowed targets".into());
+	}
+	if k == 0 {
+		return Err("there must be at least one meeting point".into());
+	}
+	if let Some(c) = crabs.iter().find(|c| c.weight < 0) {
+		return Err(format!("crab at {} has a negative weight", c.pos).into());
+	}
+
+	// crabs at the same position always move together
+	let mut by_pos = BTreeMap::new();
+	for c in crabs {
+		let total = by_pos.entry(c.pos).or_insert(0_isize);
+		*total = total.checked_add(c.weight).ok_or_else(overflow)?;
+	}
+	let groups = by_pos.into_iter().collect::<Vec<_>>();
+
+	// only the distances from a crab to an allowed target matter
+	let distances = groups
+		.iter()
+		.flat_map(|&(pos, _)| allowed.iter().map(move |&t| (pos - t).unsigned_abs()))
+		.collect::<BTreeSet<_>>();
+	let mut prev: Option<(usize, isize)> = None;
+	for d in distances {
+		let f = fuel(d)?;
+		match prev {
+			Some((pd, pf)) if f < pf => {
+				return Err(format!(
+					"fuel decreases from {} at d = {} to {} at d = {}, so crabs may not go to their nearest meeting point",
+					pf, pd, f, d
+				)
+				.into())
+			}
+			_ => prev = Some((d, f)),
+		}
+	}
+	let n = groups.len();
+	let k = k.min(n);
+
+	// best[i][j - i] is the cheapest (fuel, target) for the run of groups
+	// i..=j. With a single meeting point only the run covering everything is
+	// needed.
+	let rows = if k == 1 { 1 } else { n };
+	let mut best = Vec::with_capacity(rows);
+	for i in 0..rows {
+		let mut totals = vec![0_isize; allowed.len()];
+		let mut row = Vec::with_capacity(n - i);
+		for &(pos, weight) in &groups[i..] {
+			for (total, &t) in totals.iter_mut().zip(allowed) {
+				*total = weight
+					.checked_mul(fuel((pos - t).unsigned_abs())?)
+					.and_then(|f| total.checked_add(f))
+					.ok_or_else(overflow)?;
+			}
+			let (ix, &total) = totals
+				.iter()
+				.enumerate()
+				.min_by_key(|&(_, total)| *total)
+				.unwrap();
+			row.push((total, allowed[ix]));
+		}
+		best.push(row);
+	}
+
+	// cost[c][j] is the cheapest way to cover groups 0..=j with c + 1 runs
+	// and start[c][j] is where the last of those runs begins.
+	let mut cost = vec![vec![isize::MAX; n]; k];
+	let mut start = vec![vec![0; n]; k];
+	for j in 0..n {
+		cost[0][j] = best[0][j].0;
+	}
+	for c in 1..k {
+		for j in c..n {
+			for i in c..=j {
+				let prev = cost[c - 1][i - 1];
+				if prev == isize::MAX {
+					continue;
+				}
+				let total = prev.checked_add(best[i][j - i].0).ok_or_else(overflow)?;
+				if total < cost[c][j] {
+					cost[c][j] = total;
+					start[c][j] = i;
+				}
+			}
+		}
+	}
+
+	// walk back through the runs to find each target
+	let mut runs = Vec::with_capacity(k);
+	let mut j = n - 1;
+	for c in (0..k).rev() {
+		let i = start[c][j];
+		runs.push((groups[i].0, groups[j].0, best[i][j - i].1));
+		if c > 0 {
+			j = i - 1;
+		}
+	}
+	runs.reverse();
+
+	let moves = crabs
+		.iter()
+		.map(|c| {
+			let (_, _, t) = runs
+				.iter()
+				.find(|&&(lo, hi, _)| lo <= c.pos && c.pos <= hi)
+				.unwrap();
+			let fuel = c
+				.weight
+				.checked_mul(fuel((c.pos - t).unsigned_abs())?)
+				.ok_or_else(overflow)?;
+			Ok((*t, fuel))
+		})
+		.collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+	// runs that aren't next to each other can still share a target
+	let mut targets = runs.iter().map(|&(_, _, t)| t).collect::<Vec<_>>();
+	targets.sort_unstable();
+	targets.dedup();
+
+	Ok(Plan {
+		targets,
+		fuel: cost[k - 1][n - 1],
+		moves,
+	})
+}
+
+fn overflow() -> Box<dyn Error> {
+	"fuel overflows".into()
+}
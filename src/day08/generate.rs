@@ -0,0 +1,76 @@
+use super::glyphs::GlyphSet;
+use super::Entry;
+use aoc::rng::Rng;
+use aoc::segments::{self, Generated};
+use std::error::Error;
+
+// Generates an entry for a randomly wired display that shows the given glyphs.
+pub fn generate(glyphs: &GlyphSet, rng: &mut Rng) -> Generated {
+	let table = glyphs
+		.glyphs()
+		.iter()
+		.map(|g| (g.symbol, g.pattern.signals))
+		.collect::<Vec<_>>();
+	segments::generate(&table, glyphs.segments(), rng)
+}
+
+// Generates entries with the standard digits and makes sure each one decodes
+// back to the digits it was generated from. Returns the number of entries
+// checked.
+pub fn roundtrip(seed: u64, count: usize) -> Result<usize, Box<dyn Error>> {
+	let glyphs = GlyphSet::digits();
+	let mut rng = Rng::new(seed);
+	for i in 0..count {
+		let generated = generate(&glyphs, &mut rng);
+		let entry = generated.line.parse::<Entry>()?;
+		let decoder = entry.get_decoder()?;
+		let decoded = entry
+			.output
+			.iter()
+			.map(|&p| decoder.decode(p).map(|d| (b'0' + d) as char))
+			.collect::<Result<String, _>>()?;
+		if decoded != generated.symbols {
+			return Err(format!(
+				"entry {} of seed {}: {} decoded as {}, expected {}",
+				i + 1,
+				seed,
+				generated.line,
+				decoded,
+				generated.symbols
+			)
+			.into());
+		}
+	}
+	Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn digits_decode_back() {
+		for seed in 0..100 {
+			assert_eq!(roundtrip(seed, 100).unwrap(), 100, "seed {}", seed);
+		}
+	}
+
+	#[test]
+	fn hex_decodes_back_by_wiring() {
+		let glyphs = GlyphSet::hex();
+		for seed in 0..100 {
+			let mut rng = Rng::new(seed);
+			for _ in 0..20 {
+				let generated = generate(&glyphs, &mut rng);
+				let entry = generated.line.parse::<Entry>().unwrap();
+				assert_eq!(
+					entry.decode(&glyphs).unwrap(),
+					generated.symbols,
+					"seed {}: {}",
+					seed,
+					generated.line
+				);
+			}
+		}
+	}
+}
@@ -0,0 +1,77 @@
+use super::Pattern;
+use aoc::segments::{self, DIGITS};
+use std::error::Error;
+use std::str::FromStr;
+
+// the extra symbols shown on seven-segment displays that can show hex.
+const HEX: &str = "
+A abcdef
+b bdefg
+C abeg
+d cdefg
+E abdeg
+F abde
+";
+
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+	pub symbol: char,
+	pub pattern: Pattern,
+}
+
+// The symbols a display can show along with the segments lit for each.
+#[derive(Debug, Clone)]
+pub struct GlyphSet {
+	segments: usize,
+	glyphs: Vec<Glyph>,
+}
+
+impl GlyphSet {
+	pub fn digits() -> GlyphSet {
+		DIGITS.parse().unwrap()
+	}
+
+	pub fn hex() -> GlyphSet {
+		format!("{}{}", DIGITS, HEX).parse().unwrap()
+	}
+
+	// the number of segments on the display.
+	pub fn segments(&self) -> usize {
+		self.segments
+	}
+
+	pub fn glyphs(&self) -> &[Glyph] {
+		&self.glyphs
+	}
+
+	// the symbol that lights exactly the given segments, if any.
+	pub fn find(&self, p: Pattern) -> Option<char> {
+		self.glyphs
+			.iter()
+			.find(|g| g.pattern == p)
+			.map(|g| g.symbol)
+	}
+}
+
+// Parses a table with a symbol and the segments it lights on each line, like
+// `7 acf`, with segments::parse_glyphs. The display is assumed to have as
+// many segments as it takes to include the last letter used by any glyph.
+impl FromStr for GlyphSet {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let glyphs = segments::parse_glyphs(s)?
+			.into_iter()
+			.map(|(symbol, signals)| Glyph {
+				symbol,
+				pattern: Pattern { signals },
+			})
+			.collect::<Vec<_>>();
+		let segments = glyphs
+			.iter()
+			.map(|g| 32 - g.pattern.signals.leading_zeros() as usize)
+			.max()
+			.unwrap();
+		Ok(GlyphSet { segments, glyphs })
+	}
+}
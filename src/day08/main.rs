@@ -1,34 +1,29 @@
+use glyphs::GlyphSet;
 use std::error::Error;
+use std::fmt::Write;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader};
 use std::str::FromStr;
 
-const A: Pattern = Pattern { signals: 0x01 };
-const B: Pattern = Pattern { signals: 0x02 };
-const C: Pattern = Pattern { signals: 0x04 };
-const D: Pattern = Pattern { signals: 0x08 };
-const E: Pattern = Pattern { signals: 0x10 };
-const F: Pattern = Pattern { signals: 0x20 };
-const G: Pattern = Pattern { signals: 0x40 };
+mod generate;
+mod glyphs;
+mod render;
+mod wiring;
 
-// used for counting bits in bitset
-const BIT_COUNTS: &'static [usize] = &[0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4];
+// the most segments a display can have, one for each letter.
+const MAX_SEGMENTS: u32 = 26;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 struct Pattern {
-	signals: u8,
+	signals: u32,
 }
 
 impl Pattern {
 	fn from_char(c: char) -> Result<Pattern, Box<dyn Error>> {
 		match c {
-			'a' => Ok(A),
-			'b' => Ok(B),
-			'c' => Ok(C),
-			'd' => Ok(D),
-			'e' => Ok(E),
-			'f' => Ok(F),
-			'g' => Ok(G),
+			'a'..='z' => Ok(Pattern {
+				signals: 1 << (c as u32 - 'a' as u32),
+			}),
 			_ => Err(format!("invalid signal: {}", c).into()),
 		}
 	}
@@ -57,44 +52,19 @@ impl Pattern {
 		}
 	}
 
-	fn to_string(&self) -> String {
-		let mut s = String::new();
-		if !self.intersection(A).is_empty() {
-			s.push('a');
-		}
-		if !self.intersection(B).is_empty() {
-			s.push('b');
-		}
-		if !self.intersection(C).is_empty() {
-			s.push('c');
-		}
-		if !self.intersection(D).is_empty() {
-			s.push('d');
-		}
-		if !self.intersection(E).is_empty() {
-			s.push('e');
-		}
-		if !self.intersection(F).is_empty() {
-			s.push('f');
-		}
-		if !self.intersection(G).is_empty() {
-			s.push('g');
-		}
-		s
-	}
-
 	fn len(&self) -> usize {
-		BIT_COUNTS[self.signals as usize & 0xf] + BIT_COUNTS[(self.signals >> 4) as usize & 0xf]
-	}
-
-	fn is_empty(&self) -> bool {
-		self.signals == 0
+		self.signals.count_ones() as usize
 	}
 }
 
 impl std::fmt::Display for Pattern {
 	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-		write!(f, "{}", self.to_string())
+		for i in 0..MAX_SEGMENTS {
+			if self.signals & (1 << i) != 0 {
+				f.write_char((b'a' + i as u8) as char)?;
+			}
+		}
+		Ok(())
 	}
 }
 
@@ -115,11 +85,11 @@ impl FromStr for Entry {
 		Ok(Entry {
 			patterns: l
 				.split_whitespace()
-				.map(|s| Pattern::from_str(s))
+				.map(Pattern::from_str)
 				.collect::<Result<Vec<_>, _>>()?,
 			output: r
 				.split_whitespace()
-				.map(|s| Pattern::from_str(s))
+				.map(Pattern::from_str)
 				.collect::<Result<Vec<_>, _>>()?,
 		})
 	}
@@ -148,7 +118,7 @@ impl std::fmt::Display for Entry {
 }
 
 impl Entry {
-	fn get_decoder(&self) -> Result<Decoder, Box<dyn Error>> {
+	fn get_decoder(&self) -> Result<Decoder<'_>, Box<dyn Error>> {
 		let s1 = match self.patterns.iter().find(|s| s.len() == 2) {
 			Some(p) => p,
 			None => return Err("could not find pattern for 1".into()),
@@ -159,7 +129,60 @@ impl Entry {
 			None => return Err("could not find pattern for 4".into()),
 		};
 
-		Ok(Decoder { s1, s4 })
+		Ok(Decoder {
+			s1,
+			s4,
+			patterns: &self.patterns,
+		})
+	}
+
+	// works out the wiring from every pattern in the entry, including the output.
+	fn solve_wiring(&self, glyphs: &GlyphSet) -> wiring::Solution {
+		wiring::solve(&[&self.patterns[..], &self.output[..]].concat(), glyphs)
+	}
+
+	// Decodes the output using each of the given wirings, which only succeeds
+	// if they all agree on every symbol.
+	fn decode_with(
+		&self,
+		wirings: &[wiring::Wiring],
+		glyphs: &GlyphSet,
+	) -> Result<String, Box<dyn Error>> {
+		let mut value: Option<String> = None;
+		for w in wirings {
+			let v = self
+				.output
+				.iter()
+				.enumerate()
+				.map(|(i, &p)| {
+					w.decode(p, glyphs).ok_or_else(|| {
+						format!("output {}: {} is not a glyph with wiring {}", i + 1, p, w)
+					})
+				})
+				.collect::<Result<String, _>>()?;
+			match value {
+				Some(prev) if prev != v => {
+					return Err(format!("ambiguous wiring for {}: {} or {}", self, prev, v).into())
+				}
+				_ => value = Some(v),
+			}
+		}
+		value.ok_or_else(|| format!("no wiring for {}", self).into())
+	}
+
+	// decodes the output by solving for the wiring.
+	fn decode(&self, glyphs: &GlyphSet) -> Result<String, Box<dyn Error>> {
+		match self.solve_wiring(glyphs) {
+			wiring::Solution::Unique(w) => self.decode_with(&[w], glyphs),
+			wiring::Solution::Ambiguous(ws) => self.decode_with(&ws, glyphs),
+			// without the output, the wiring may be found and the bad output
+			// pointed out
+			wiring::Solution::Impossible => match wiring::solve(&self.patterns, glyphs) {
+				wiring::Solution::Unique(w) => self.decode_with(&[w], glyphs),
+				wiring::Solution::Ambiguous(ws) => self.decode_with(&ws, glyphs),
+				wiring::Solution::Impossible => Err(format!("no wiring for {}", self).into()),
+			},
+		}
 	}
 }
 
@@ -168,46 +191,81 @@ struct Decoder<'a> {
 	// we only need to know the set of segments for 1 and 4 to deduce everything else
 	s1: &'a Pattern,
 	s4: &'a Pattern,
+	// but a pattern that isn't a digit can still have the right overlaps, so
+	// only the patterns the entry shows for its digits are decoded
+	patterns: &'a [Pattern],
 }
 
-impl<'a> Decoder<'a> {
-	fn decode(&self, p: Pattern) -> u8 {
-		match p.len() {
-			2 => 1,
-			3 => 7,
-			4 => 4,
-			7 => 8,
-			6 => {
-				// patterns with 6 signals include 6, 0 and 9.
-				// 6 only shares 1 signal with 1
-				// 0 shares 3 segments with 4
-				// 9 shares 4 segments with 4
-				if self.s1.intersection(p).len() == 1 {
-					6
-				} else if self.s4.intersection(p).len() == 3 {
-					0
-				} else {
-					9
-				}
-			}
-			_ => {
-				// patterns with 5 siganls include 3, 2, and 5
-				// 3 shares 2 signals with 1
-				// 2 shares 2 signals with 4
-				// 5 shares 3 signals with 4
-				if self.s1.intersection(p).len() == 2 {
-					3
-				} else if self.s4.intersection(p).len() == 2 {
-					2
-				} else {
-					5
-				}
+#[derive(Debug, Clone, Copy)]
+enum DecodeError {
+	// the pattern uses a wire past g
+	InvalidWire(Pattern),
+	// no digit has this many segments
+	InvalidLength(Pattern),
+	// the pattern can't be a digit given the patterns for 1 and 4
+	Inconsistent(Pattern),
+	// the pattern isn't one the entry shows for its digits
+	Unseen(Pattern),
+}
+
+impl std::fmt::Display for DecodeError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			DecodeError::InvalidWire(p) => write!(f, "{} uses a wire past g", p),
+			DecodeError::InvalidLength(p) => write!(f, "no digit has {} segments: {}", p.len(), p),
+			DecodeError::Inconsistent(p) => {
+				write!(
+					f,
+					"{} does not match any digit given the patterns for 1 and 4",
+					p
+				)
 			}
+			DecodeError::Unseen(p) => write!(f, "{} is not one of the entry's patterns", p),
 		}
 	}
 }
 
-fn read_entries<'a, R: io::Read>(r: R) -> Result<Vec<Entry>, Box<dyn Error>> {
+impl Error for DecodeError {}
+
+impl<'a> Decoder<'a> {
+	fn decode(&self, p: Pattern) -> Result<u8, DecodeError> {
+		if p.signals & !0x7f != 0 {
+			return Err(DecodeError::InvalidWire(p));
+		}
+		if !self.patterns.contains(&p) {
+			return Err(DecodeError::Unseen(p));
+		}
+
+		// the number of signals p shares with 1 and 4, which is enough to tell
+		// apart any two digits with the same number of segments
+		let shared = (self.s1.intersection(p).len(), self.s4.intersection(p).len());
+		let digit = match (p.len(), shared) {
+			(2, (2, 2)) => 1,
+			(3, (2, 2)) => 7,
+			(4, (2, 4)) => 4,
+			(7, (2, 4)) => 8,
+			// patterns with 6 signals include 6, 0 and 9.
+			// 6 only shares 1 signal with 1
+			// 0 shares 3 segments with 4
+			// 9 shares 4 segments with 4
+			(6, (1, 3)) => 6,
+			(6, (2, 3)) => 0,
+			(6, (2, 4)) => 9,
+			// patterns with 5 siganls include 3, 2, and 5
+			// 3 shares 2 signals with 1
+			// 2 shares 2 signals with 4
+			// 5 shares 3 signals with 4
+			(5, (2, 3)) => 3,
+			(5, (1, 2)) => 2,
+			(5, (1, 3)) => 5,
+			(2..=7, _) => return Err(DecodeError::Inconsistent(p)),
+			_ => return Err(DecodeError::InvalidLength(p)),
+		};
+		Ok(digit)
+	}
+}
+
+fn read_entries<R: io::Read>(r: R) -> Result<Vec<Entry>, Box<dyn Error>> {
 	let r = BufReader::new(r);
 	r.lines().map(|line| line?.parse::<Entry>()).collect()
 }
@@ -230,17 +288,52 @@ fn part1(entries: &[Entry]) -> usize {
 fn part2(entries: &[Entry]) -> Result<usize, Box<dyn Error>> {
 	entries
 		.iter()
-		.map(|e| {
-			let decoder = e.get_decoder()?;
-			Ok(e.output
-				.iter()
-				.enumerate()
-				.map(|(i, &p)| (10_usize).pow(3 - i as u32) * decoder.decode(p) as usize)
-				.sum::<usize>())
+		.enumerate()
+		.map(|(i, e)| {
+			let decoder = e
+				.get_decoder()
+				.map_err(|err| format!("entry {}: {}", i + 1, err))?;
+			e.output.iter().enumerate().try_fold(0, |v, (j, &p)| {
+				let d = decoder
+					.decode(p)
+					.map_err(|err| format!("entry {}, output {}: {}", i + 1, j + 1, err))?;
+				Ok(v * 10 + d as usize)
+			})
+		})
+		.sum()
+}
+
+// Like part2 but solves for the full wiring of each entry rather than relying
+// on the patterns for 1 and 4.
+fn part2_by_wiring(entries: &[Entry], glyphs: &GlyphSet) -> Result<usize, Box<dyn Error>> {
+	entries
+		.iter()
+		.enumerate()
+		.map(|(i, e)| {
+			let v = e
+				.decode(glyphs)
+				.map_err(|err| format!("entry {}: {}", i + 1, err))?;
+			v.parse::<usize>()
+				.map_err(|_| format!("entry {}: {} is not a decimal number", i + 1, v).into())
 		})
 		.sum()
 }
 
+fn generator_args<'a, 'b>() -> Vec<clap::Arg<'a, 'b>> {
+	vec![
+		clap::Arg::with_name("seed")
+			.long("seed")
+			.takes_value(true)
+			.default_value("0")
+			.help("the seed for the random wirings"),
+		clap::Arg::with_name("count")
+			.long("count")
+			.takes_value(true)
+			.default_value("200")
+			.help("the number of entries"),
+	]
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
 	let matches = clap::App::new("day08")
 		.arg(
@@ -248,14 +341,120 @@ fn main() -> Result<(), Box<dyn Error>> {
 				.takes_value(true)
 				.help("the input file"),
 		)
+		.arg(
+			clap::Arg::with_name("solver")
+				.long("solver")
+				.takes_value(true)
+				.possible_values(&["heuristic", "wiring"])
+				.default_value("heuristic")
+				.help("how the digits in part 2 are decoded"),
+		)
+		.arg(
+			clap::Arg::with_name("wiring")
+				.long("wiring")
+				.help("show the wiring recovered for each entry"),
+		)
+		.arg(
+			clap::Arg::with_name("glyphs")
+				.long("glyphs")
+				.takes_value(true)
+				.default_value("digits")
+				.help("the symbols the display shows: digits, hex or a file of glyphs"),
+		)
+		.arg(
+			clap::Arg::with_name("decode")
+				.long("decode")
+				.help("show the decoded output of each entry"),
+		)
+		.arg(
+			clap::Arg::with_name("view")
+				.long("view")
+				.help("draw the patterns, wiring and decoded output of each entry"),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("generate")
+				.about("print randomly wired entries for the display")
+				.args(&generator_args()),
+		)
+		.subcommand(
+			clap::SubCommand::with_name("roundtrip")
+				.about("check that randomly wired entries decode to the digits they show")
+				.args(&generator_args()),
+		)
 		.get_matches();
 
+	let glyphs = match matches.value_of("glyphs").unwrap() {
+		"digits" => GlyphSet::digits(),
+		"hex" => GlyphSet::hex(),
+		path => std::fs::read_to_string(path)?.parse::<GlyphSet>()?,
+	};
+
+	match matches.subcommand() {
+		("generate", Some(args)) => {
+			let mut rng = aoc::rng::Rng::new(args.value_of("seed").unwrap().parse()?);
+			for _ in 0..args.value_of("count").unwrap().parse::<usize>()? {
+				println!("{}", generate::generate(&glyphs, &mut rng).line);
+			}
+			return Ok(());
+		}
+		("roundtrip", Some(args)) => {
+			let n = generate::roundtrip(
+				args.value_of("seed").unwrap().parse()?,
+				args.value_of("count").unwrap().parse()?,
+			)?;
+			println!("{} entries decoded", n);
+			return Ok(());
+		}
+		_ => {}
+	}
+
 	let entries = read_entries(File::open(
 		matches.value_of("input").unwrap_or("data/day08/input.txt"),
 	)?)?;
 
-	println!("Part 1: {}", part1(&entries));
-	println!("Part 2: {}", part2(&entries)?);
+	// the puzzle's answers only make sense for displays that show digits, and
+	// they're left out when showing entries since those may not be decodable
+	let showing = ["wiring", "decode", "view"]
+		.iter()
+		.any(|&mode| matches.is_present(mode));
+	if !showing && matches.value_of("glyphs") == Some("digits") {
+		println!("Part 1: {}", part1(&entries));
+		match matches.value_of("solver") {
+			Some("wiring") => println!("Part 2: {}", part2_by_wiring(&entries, &glyphs)?),
+			_ => println!("Part 2: {}", part2(&entries)?),
+		}
+	}
+
+	if matches.is_present("wiring") {
+		for (i, e) in entries.iter().enumerate() {
+			match e.solve_wiring(&glyphs) {
+				wiring::Solution::Unique(w) => println!("Entry {}: {}", i + 1, w),
+				wiring::Solution::Ambiguous(ws) => {
+					println!("Entry {}: ambiguous, {} wirings", i + 1, ws.len());
+					for w in ws {
+						println!("  {}", w);
+					}
+				}
+				wiring::Solution::Impossible => println!("Entry {}: no wiring", i + 1),
+			}
+		}
+	}
+
+	if matches.is_present("decode") {
+		for (i, e) in entries.iter().enumerate() {
+			match e.decode(&glyphs) {
+				Ok(v) => println!("Entry {}: {}", i + 1, v),
+				Err(err) => println!("Entry {}: {}", i + 1, err),
+			}
+		}
+	}
+
+	if matches.is_present("view") {
+		for (i, e) in entries.iter().enumerate() {
+			println!("Entry {}", i + 1);
+			println!("{}", render::view(e, &glyphs));
+		}
+	}
 
 	Ok(())
 }
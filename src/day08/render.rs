@@ -0,0 +1,110 @@
+use super::glyphs::GlyphSet;
+use super::wiring::{self, Wiring};
+use super::{Entry, Pattern};
+
+// The segments of the display in the same layout as the puzzle, where each
+// letter marks a cell of the segment it names.
+const LAYOUT: &str = "
+ aaaa
+b    c
+b    c
+ dddd
+e    f
+e    f
+ gggg
+";
+
+// the size of a drawn display
+const WIDTH: usize = 6;
+const HEIGHT: usize = 7;
+
+// Draws the pattern as a seven-segment display, filling lit segments with
+// their letter and leaving unlit ones as dots. Only segments a through g
+// have a place on the display, so any others are not drawn.
+pub fn render(p: Pattern) -> Vec<String> {
+	LAYOUT
+		.lines()
+		.skip(1)
+		.map(|row| {
+			format!("{:width$}", row, width = WIDTH)
+				.chars()
+				.map(|c| match c {
+					'a'..='g' if p.signals & (1 << (c as u32 - 'a' as u32)) != 0 => c,
+					'a'..='g' => '.',
+					_ => c,
+				})
+				.collect()
+		})
+		.collect()
+}
+
+// Places drawn displays next to each other with a label centered under each.
+// A column is widened when its label doesn't fit under the display.
+fn side_by_side(displays: &[(Vec<String>, String)]) -> String {
+	let widths = displays
+		.iter()
+		.map(|(_, label)| label.chars().count().max(WIDTH))
+		.collect::<Vec<_>>();
+	let mut s = String::new();
+	for row in 0..HEIGHT {
+		let cells = displays
+			.iter()
+			.zip(&widths)
+			.map(|((rows, _), &w)| format!("{:^w$}", rows[row], w = w))
+			.collect::<Vec<_>>();
+		s.push_str(cells.join("  ").trim_end());
+		s.push('\n');
+	}
+	let labels = displays
+		.iter()
+		.zip(&widths)
+		.map(|((_, label), &w)| format!("{:^w$}", label, w = w))
+		.collect::<Vec<_>>();
+	s.push_str(labels.join("  ").trim_end());
+	s.push('\n');
+	s
+}
+
+// Shows an entry as it looks on the display: the ten observed patterns as
+// wired, the wiring recovered from them and the output once unscrambled
+// along with the symbols it decodes to.
+pub fn view(e: &Entry, glyphs: &GlyphSet) -> String {
+	let observed = e
+		.patterns
+		.iter()
+		.map(|&p| (render(p), p.to_string()))
+		.collect::<Vec<_>>();
+	let mut s = side_by_side(&observed);
+	s.push('\n');
+
+	let w: Option<Wiring> = match e.solve_wiring(glyphs) {
+		wiring::Solution::Unique(w) => {
+			s.push_str(&format!("wiring: {}\n", w));
+			Some(w)
+		}
+		wiring::Solution::Ambiguous(ws) => {
+			s.push_str(&format!("wiring: ambiguous, {} wirings\n", ws.len()));
+			None
+		}
+		wiring::Solution::Impossible => {
+			s.push_str("wiring: none\n");
+			None
+		}
+	};
+	s.push('\n');
+
+	// without a wiring the output can only be drawn as it was observed
+	let output = e
+		.output
+		.iter()
+		.map(|&p| match &w {
+			Some(w) => {
+				let symbol = w.decode(p, glyphs).unwrap_or('?');
+				(render(w.unscramble(p)), symbol.to_string())
+			}
+			None => (render(p), "?".to_string()),
+		})
+		.collect::<Vec<_>>();
+	s.push_str(&side_by_side(&output));
+	s
+}
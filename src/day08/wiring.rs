@@ -0,0 +1,209 @@
+use super::glyphs::GlyphSet;
+use super::Pattern;
+use std::fmt;
+
+// A mapping from each scrambled wire to the segment it actually drives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wiring {
+	segments: Vec<u8>,
+}
+
+impl Wiring {
+	// the pattern that is actually lit when the given wires are on.
+	pub fn unscramble(&self, p: Pattern) -> Pattern {
+		let mut signals = 0;
+		for (wire, &segment) in self.segments.iter().enumerate() {
+			if p.signals & (1 << wire) != 0 {
+				signals |= 1 << segment;
+			}
+		}
+		Pattern { signals }
+	}
+
+	// the symbol shown by the given wires, if any.
+	pub fn decode(&self, p: Pattern, glyphs: &GlyphSet) -> Option<char> {
+		if p.signals
+			.checked_shr(self.segments.len() as u32)
+			.unwrap_or(0)
+			!= 0
+		{
+			// some wire isn't connected to anything
+			return None;
+		}
+		glyphs.find(self.unscramble(p))
+	}
+}
+
+impl fmt::Display for Wiring {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (wire, &segment) in self.segments.iter().enumerate() {
+			if wire > 0 {
+				write!(f, " ")?;
+			}
+			write!(
+				f,
+				"{}→{}",
+				(b'a' + wire as u8) as char,
+				(b'a' + segment) as char
+			)?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug)]
+pub enum Solution {
+	Unique(Wiring),
+	// every wiring that is consistent with the observed patterns
+	Ambiguous(Vec<Wiring>),
+	// no wiring makes every observed pattern a glyph
+	Impossible,
+}
+
+fn bits(v: u32) -> impl Iterator<Item = usize> {
+	(0..32).filter(move |i| v & (1 << i) != 0)
+}
+
+// The segments each wire could still drive. A wiring has been found once
+// every wire has exactly one possible segment.
+#[derive(Debug, Clone)]
+struct Domains {
+	domains: Vec<u32>,
+	// a mask with a bit for every segment on the display
+	all: u32,
+}
+
+impl Domains {
+	// Narrows the domains using every observed pattern until nothing else
+	// changes. Returns false if some wire is left with no possible segment.
+	fn propagate(&mut self, observed: &[u32], glyphs: &GlyphSet) -> bool {
+		loop {
+			let before = self.domains.clone();
+
+			for &p in observed {
+				// the glyphs this pattern could still be
+				let mut lit = 0;
+				let mut unlit = 0;
+				for g in glyphs.glyphs() {
+					let g = g.pattern.signals;
+					if self.could_show(p, g) {
+						lit |= g;
+						unlit |= !g & self.all;
+					}
+				}
+				for (wire, domain) in self.domains.iter_mut().enumerate() {
+					if p & (1 << wire) != 0 {
+						*domain &= lit;
+					} else {
+						*domain &= unlit;
+					}
+				}
+			}
+
+			// a segment that is the only choice for one wire is taken
+			for wire in 0..self.domains.len() {
+				let d = self.domains[wire];
+				if d.count_ones() == 1 {
+					for (other, domain) in self.domains.iter_mut().enumerate() {
+						if other != wire {
+							*domain &= !d;
+						}
+					}
+				}
+			}
+
+			if self.domains.contains(&0) {
+				return false;
+			}
+			if self.domains == before {
+				return true;
+			}
+		}
+	}
+
+	// whether wires p could be showing glyph g given the current domains.
+	fn could_show(&self, p: u32, g: u32) -> bool {
+		if p.count_ones() != g.count_ones() {
+			return false;
+		}
+		self.domains.iter().enumerate().all(|(wire, &domain)| {
+			if p & (1 << wire) != 0 {
+				domain & g != 0
+			} else {
+				domain & !g & self.all != 0
+			}
+		})
+	}
+
+	fn wiring(&self) -> Option<Wiring> {
+		self.domains
+			.iter()
+			.map(|&d| {
+				if d.count_ones() == 1 {
+					Some(d.trailing_zeros() as u8)
+				} else {
+					None
+				}
+			})
+			.collect::<Option<Vec<_>>>()
+			.map(|segments| Wiring { segments })
+	}
+
+	fn search(mut self, observed: &[u32], glyphs: &GlyphSet, found: &mut Vec<Wiring>) {
+		if !self.propagate(observed, glyphs) {
+			return;
+		}
+
+		if let Some(wiring) = self.wiring() {
+			let shows_glyphs = observed
+				.iter()
+				.all(|&p| wiring.decode(Pattern { signals: p }, glyphs).is_some());
+			if shows_glyphs {
+				found.push(wiring);
+			}
+			return;
+		}
+
+		// branch on the wire with the fewest choices left
+		let (wire, &domain) = self
+			.domains
+			.iter()
+			.enumerate()
+			.filter(|(_, d)| d.count_ones() > 1)
+			.min_by_key(|(_, d)| d.count_ones())
+			.unwrap();
+		for segment in bits(domain) {
+			let mut next = self.clone();
+			next.domains[wire] = 1 << segment;
+			next.search(observed, glyphs, found);
+		}
+	}
+}
+
+// Finds every wiring under which each observed pattern shows one of the
+// glyphs. This works from any subset of the patterns, though fewer patterns
+// are less likely to pin down a single wiring.
+pub fn solve(observed: &[Pattern], glyphs: &GlyphSet) -> Solution {
+	let n = glyphs.segments();
+	let all = if n >= 32 { u32::MAX } else { (1 << n) - 1 };
+
+	let mut patterns = observed.iter().map(|p| p.signals).collect::<Vec<_>>();
+	patterns.sort_unstable();
+	patterns.dedup();
+	if patterns.iter().any(|&p| p & !all != 0) {
+		// some pattern uses a wire the display doesn't have
+		return Solution::Impossible;
+	}
+
+	let mut found = Vec::new();
+	Domains {
+		domains: vec![all; n],
+		all,
+	}
+	.search(&patterns, glyphs, &mut found);
+	match found.len() {
+		0 => Solution::Impossible,
+		1 => Solution::Unique(found.pop().unwrap()),
+		_ => Solution::Ambiguous(found),
+	}
+}
@@ -0,0 +1,169 @@
+use super::label::Labels;
+use super::Map;
+use aoc::grid::{Grid, Pt};
+use std::io::{self, Write};
+
+// the color of the walls between basins and of the low points.
+const WALL: [u8; 3] = [40, 40, 40];
+const LOW_POINT: [u8; 3] = [255, 255, 255];
+
+// Writes the heights as a binary PGM where higher points are brighter. PGM
+// only has room for 16 bits, so heights any higher than that are scaled.
+// See http://netpbm.sourceforge.net/doc/pgm.html
+pub fn write_pgm<W: Write>(map: &Map, w: &mut W) -> io::Result<()> {
+	let (width, height) = map.size();
+	let max = max_height(map);
+	let maxval = max.min(u16::MAX as u32);
+	let scale = |h: u32| (h as u64 * maxval as u64 / max as u64) as u16;
+	write!(w, "P5\n{} {}\n{}\n", width, height, maxval)?;
+	if maxval < 256 {
+		w.write_all(
+			&map.heights
+				.iter()
+				.map(|(_, &h)| scale(h) as u8)
+				.collect::<Vec<_>>(),
+		)
+	} else {
+		w.write_all(
+			&map.heights
+				.iter()
+				.flat_map(|(_, &h)| scale(h).to_be_bytes())
+				.collect::<Vec<_>>(),
+		)
+	}
+}
+
+// the height of the highest point, or 1 for a flat map so heights can be
+// scaled by it.
+fn max_height(map: &Map) -> u32 {
+	map.heights
+		.iter()
+		.map(|(_, &h)| h)
+		.max()
+		.unwrap_or(0)
+		.max(1)
+}
+
+// Writes the map as a binary PPM with each basin in its own color, shaded
+// darker as the ground rises, and the low points in white.
+// See http://netpbm.sourceforge.net/doc/ppm.html
+pub fn write_ppm<W: Write>(
+	map: &Map,
+	labels: &Labels,
+	low_points: &[(Pt, u32)],
+	w: &mut W,
+) -> io::Result<()> {
+	let (width, height) = map.size();
+	write!(w, "P6\n{} {}\n255\n", width, height)?;
+	let colors = Colors::new(map, labels, low_points);
+	w.write_all(
+		&map.heights
+			.points()
+			.flat_map(|pt| colors.of(&pt))
+			.collect::<Vec<_>>(),
+	)
+}
+
+// Writes the heights with each basin in its own color using ANSI escapes,
+// for viewing the map in a terminal.
+pub fn write_ansi<W: Write>(
+	map: &Map,
+	labels: &Labels,
+	low_points: &[(Pt, u32)],
+	w: &mut W,
+) -> io::Result<()> {
+	let colors = Colors::new(map, labels, low_points);
+	// heights with more than one digit are padded so the columns line up
+	let width = max_height(map).to_string().len();
+	let sep = if width > 1 { " " } else { "" };
+	for (y, row) in map.heights.rows().enumerate() {
+		for (x, h) in row.iter().enumerate() {
+			if x > 0 {
+				write!(w, "{}", sep)?;
+			}
+			let pt = (x, y);
+			let [r, g, b] = colors.of(&pt);
+			if colors.low_points[pt] {
+				// low points are bold on a dark background so they stand out
+				write!(
+					w,
+					"\x1b[1;38;2;{};{};{};48;2;0;0;0m{:>width$}",
+					r,
+					g,
+					b,
+					h,
+					width = width
+				)?;
+			} else {
+				write!(
+					w,
+					"\x1b[38;2;{};{};{}m{:>width$}",
+					r,
+					g,
+					b,
+					h,
+					width = width
+				)?;
+			}
+			write!(w, "\x1b[0m")?;
+		}
+		writeln!(w)?;
+	}
+	Ok(())
+}
+
+struct Colors<'a> {
+	map: &'a Map,
+	labels: &'a Labels,
+	low_points: Grid<bool>,
+	max: u32,
+}
+
+impl<'a> Colors<'a> {
+	fn new(map: &'a Map, labels: &'a Labels, low_points: &'a [(Pt, u32)]) -> Colors<'a> {
+		let (width, height) = map.size();
+		let mut lows = Grid::filled(width, height, false);
+		for (pt, _) in low_points {
+			lows[*pt] = true;
+		}
+		Colors {
+			map,
+			labels,
+			low_points: lows,
+			max: max_height(map),
+		}
+	}
+
+	fn of(&self, pt: &Pt) -> [u8; 3] {
+		if self.low_points[*pt] {
+			return LOW_POINT;
+		}
+		match self.labels.get(pt) {
+			Some(basin) => {
+				let v = 1.0 - 0.6 * self.map.get(pt) as f64 / self.max as f64;
+				hue(basin, v)
+			}
+			None => WALL,
+		}
+	}
+}
+
+// A color for the nth basin with brightness v. Stepping the hue by the golden
+// angle keeps basins with nearby labels, which are usually next to each
+// other, from getting similar colors.
+fn hue(n: usize, v: f64) -> [u8; 3] {
+	let h = (n as f64 * 137.508) % 360.0 / 60.0;
+	let s = 0.7;
+	let c = v * s;
+	let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+	let (r, g, b) = match h as usize {
+		0 => (c, x, 0.0),
+		1 => (x, c, 0.0),
+		2 => (0.0, c, x),
+		3 => (0.0, x, c),
+		4 => (x, 0.0, c),
+		_ => (c, 0.0, x),
+	};
+	let m = v - c;
+	[r, g, b].map(|c| ((c + m) * 255.0).round() as u8)
+}
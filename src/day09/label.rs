@@ -0,0 +1,113 @@
+use super::Map;
+use aoc::grid::{Grid, Pt};
+
+// Labels are kept as u32 rather than Option<usize> so that the labels of a
+// map with tens of millions of points only take four bytes a point.
+const NONE: u32 = u32::MAX;
+
+// A disjoint set of provisional labels.
+// See https://en.wikipedia.org/wiki/Disjoint-set_data_structure
+struct UnionFind {
+	parents: Vec<u32>,
+}
+
+impl UnionFind {
+	fn new() -> UnionFind {
+		UnionFind {
+			parents: Vec::new(),
+		}
+	}
+
+	fn make(&mut self) -> u32 {
+		let ix = self.parents.len() as u32;
+		self.parents.push(ix);
+		ix
+	}
+
+	fn find(&mut self, mut ix: u32) -> u32 {
+		while self.parents[ix as usize] != ix {
+			// path halving
+			self.parents[ix as usize] = self.parents[self.parents[ix as usize] as usize];
+			ix = self.parents[ix as usize];
+		}
+		ix
+	}
+
+	fn union(&mut self, a: u32, b: u32) -> u32 {
+		let (a, b) = (self.find(a), self.find(b));
+		// the smaller label always becomes the root, which keeps the final
+		// labels in raster order
+		let (root, child) = if a < b { (a, b) } else { (b, a) };
+		self.parents[child as usize] = root;
+		root
+	}
+}
+
+// The basin each point of a map belongs to along with the size of every basin.
+#[derive(Debug)]
+pub struct Labels {
+	// the basin of each point, or NONE for the walls between basins
+	labels: Grid<u32>,
+	sizes: Vec<usize>,
+}
+
+impl Labels {
+	// Labels the basins of the map with two passes over the points. The first
+	// gives each point the label of a neighbor that has already been visited,
+	// noting when those neighbors are in the same basin. The second resolves
+	// every label to its basin and counts the points in each.
+	// See https://en.wikipedia.org/wiki/Connected-component_labeling#Two-pass
+	pub fn new(map: &Map) -> Labels {
+		let (w, h) = map.size();
+		let mut sets = UnionFind::new();
+		let mut labels = Grid::filled(w, h, NONE);
+		let mut neighbors = Vec::with_capacity(8);
+		for y in 0..h {
+			for x in 0..w {
+				if map.is_wall(&(x, y)) {
+					continue;
+				}
+				neighbors.clear();
+				map.collect_neighbors_of(&mut neighbors, &(x, y));
+				let label = neighbors
+					.iter()
+					.filter(|&&(nx, ny)| (ny, nx) < (y, x))
+					.map(|&pt| labels[pt])
+					.filter(|&l| l != NONE)
+					.reduce(|a, b| sets.union(a, b));
+				labels[(x, y)] = label.unwrap_or_else(|| sets.make());
+			}
+		}
+
+		// number the basins in the order they are first seen
+		let mut basins = vec![NONE; sets.parents.len()];
+		let mut sizes = Vec::new();
+		for (_, label) in labels.iter_mut() {
+			if *label == NONE {
+				continue;
+			}
+			let root = sets.find(*label) as usize;
+			if basins[root] == NONE {
+				basins[root] = sizes.len() as u32;
+				sizes.push(0);
+			}
+			sizes[basins[root] as usize] += 1;
+			*label = basins[root];
+		}
+
+		Labels { labels, sizes }
+	}
+
+	// the basin containing the point, if it isn't a wall.
+	pub fn get(&self, pt: &Pt) -> Option<usize> {
+		match self.labels[*pt] {
+			NONE => None,
+			l => Some(l as usize),
+		}
+	}
+
+	// the number of points in each basin, indexed by label.
+	pub fn sizes(&self) -> &[usize] {
+		&self.sizes
+	}
+}
@@ -1,23 +1,30 @@
-use std::collections::{BinaryHeap, HashSet};
+use aoc::grid::{Grid, Pt};
+use rules::{Neighborhood, Wall};
+use std::collections::{BinaryHeap, VecDeque};
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io;
+use std::io::BufWriter;
 
-type Pt = (usize, usize);
+mod export;
+mod label;
+mod rules;
+mod watershed;
 
 mod flood {
 	use super::Pt;
-	use std::collections::{HashSet, VecDeque};
+	use aoc::grid::Grid;
+	use std::collections::VecDeque;
 
 	fn scan<F>(
 		s: &mut VecDeque<(isize, isize)>,
-		g: &HashSet<Pt>,
+		g: &Grid<bool>,
 		lx: isize,
 		rx: isize,
 		y: isize,
 		is_inside: F,
 	) where
-		F: Fn(isize, isize, &HashSet<Pt>) -> bool,
+		F: Fn(isize, isize, &Grid<bool>) -> bool,
 	{
 		let mut added = false;
 		for x in lx..=rx {
@@ -30,121 +37,200 @@ mod flood {
 		}
 	}
 
-	// Implements a scan filling strategy for flood fill.
+	// Implements a scan filling strategy for flood fill. Points are marked in
+	// g as they're filled, so one grid can be shared by fills of regions
+	// that don't overlap, and the points filled are returned.
 	// See https://en.wikipedia.org/wiki/Flood_fill#Span_Filling
-	pub fn fill<F>(pt: &Pt, is_inside: F) -> HashSet<Pt>
+	pub fn fill<F>(pt: &Pt, g: &mut Grid<bool>, is_inside: F) -> Vec<Pt>
 	where
-		F: Fn(isize, isize, &HashSet<Pt>) -> bool,
+		F: Fn(isize, isize, &Grid<bool>) -> bool,
 	{
 		let (x, y) = pt;
 
 		let mut s = VecDeque::new();
 		s.push_back((*x as isize, *y as isize));
 
-		let mut g = HashSet::new();
+		let mut filled = Vec::new();
 
 		while let Some((mut x, y)) = s.pop_back() {
 			let mut lx = x;
-			while is_inside(lx - 1, y, &g) {
-				g.insert((lx as usize - 1, y as usize));
+			while is_inside(lx - 1, y, g) {
+				g[(lx as usize - 1, y as usize)] = true;
+				filled.push((lx as usize - 1, y as usize));
 				lx -= 1;
 			}
-			while is_inside(x, y, &g) {
-				g.insert((x as usize, y as usize));
+			while is_inside(x, y, g) {
+				g[(x as usize, y as usize)] = true;
+				filled.push((x as usize, y as usize));
 				x += 1;
 			}
-			scan(&mut s, &g, lx, x - 1, y + 1, &is_inside);
-			scan(&mut s, &g, lx, x - 1, y - 1, &is_inside);
+			scan(&mut s, g, lx, x - 1, y + 1, &is_inside);
+			scan(&mut s, g, lx, x - 1, y - 1, &is_inside);
 		}
 
-		g
+		filled
 	}
 }
 
 #[derive(Debug)]
 struct Map {
-	heights: Vec<u8>,
-	stride: usize,
+	heights: Grid<u32>,
+	neighborhood: Neighborhood,
+	wall: Wall,
 }
 
 impl Map {
-	fn from_reader<R: io::Read>(r: R) -> Result<Map, Box<dyn Error>> {
-		let r = io::BufReader::new(r);
+	// Reads a map with either a digit for each height, like the puzzle, or
+	// heights of any size separated by commas or whitespace.
+	fn from_reader<R: io::Read>(mut r: R) -> Result<Map, Box<dyn Error>> {
+		let mut s = String::new();
+		r.read_to_string(&mut s)?;
+		let heights = Map::parse_heights(&s)?;
+		if heights.width() == 0 {
+			return Err("the map is empty".into());
+		}
+		Ok(Map {
+			heights,
+			neighborhood: Neighborhood::Four,
+			wall: Wall::AtLeast(9),
+		})
+	}
+
+	// Reads rows of single digits like the puzzle's, or rows of heights of any
+	// size separated by commas or whitespace. The format is decided by the
+	// whole input: if any row has a separator, every row is read as separated
+	// heights, otherwise every row is read as digits. So a single column of
+	// heights above 9 needs a separator somewhere, like 10,\n12. Whitespace
+	// around a row is ignored, and every row has to have as many heights.
+	fn parse_heights(s: &str) -> Result<Grid<u32>, Box<dyn Error>> {
+		let separated = s.lines().any(|line| line.trim().contains([',', ' ', '\t']));
+		let mut width = None;
 		let mut heights = Vec::new();
-		let mut stride = 0;
-		for line in r.lines() {
-			let line = line?;
-			stride = line.len();
-			heights.reserve(heights.len() + stride);
-			for c in line.chars() {
-				heights.push(match c.to_digit(10) {
-					Some(v) => v as u8,
-					None => return Err(format!("invalid height: {}", c).into()),
-				});
+		for (i, line) in s.lines().enumerate() {
+			let line = line.trim();
+			let n = heights.len();
+			if separated {
+				let fields = line
+					.split(|c: char| c == ',' || c.is_whitespace())
+					.filter(|f| !f.is_empty());
+				for (j, field) in fields.enumerate() {
+					heights.push(field.parse::<u32>().map_err(|_| {
+						format!(
+							"line {}, column {}: invalid height: {}",
+							i + 1,
+							j + 1,
+							field
+						)
+					})?);
+				}
+			} else {
+				for (j, c) in line.chars().enumerate() {
+					heights.push(c.to_digit(10).ok_or_else(|| {
+						format!("line {}, column {}: invalid height: {}", i + 1, j + 1, c)
+					})?);
+				}
 			}
-		}
 
-		Ok(Map { heights, stride })
+			let n = heights.len() - n;
+			match width {
+				None => width = Some(n),
+				// a row of digits among separated rows is read as one height
+				Some(w) if w != n && separated && !line.contains([',', ' ', '\t']) => {
+					return Err(format!(
+						"line {}: expected {} heights, found a row without separators",
+						i + 1,
+						w
+					)
+					.into())
+				}
+				Some(w) if w != n => {
+					return Err(
+						format!("line {}: expected {} heights, found {}", i + 1, w, n).into(),
+					)
+				}
+				_ => {}
+			}
+		}
+		Grid::new(width.unwrap_or(0), heights)
 	}
 
-	fn get(&self, pt: &Pt) -> u8 {
-		let (x, y) = pt;
-		self.heights[y * self.stride + x]
+	fn get(&self, pt: &Pt) -> u32 {
+		self.heights[*pt]
 	}
 
 	fn size(&self) -> (usize, usize) {
-		(self.stride, self.heights.len() / self.stride)
+		self.heights.size()
 	}
 
-	fn collect_neighbors_of(&self, dst: &mut Vec<Pt>, pt: &Pt) {
-		let (w, h) = self.size();
-		let xlim = w - 1;
-		let ylim = h - 1;
+	fn is_wall(&self, pt: &Pt) -> bool {
+		self.wall.contains(pt, self.get(pt))
+	}
 
-		let (x, y) = *pt;
-		if y > 0 {
-			dst.push((x, y - 1));
-		}
-		if y < ylim {
-			dst.push((x, y + 1));
-		}
-		if x > 0 {
-			dst.push((x - 1, y));
-		}
-		if x < xlim {
-			dst.push((x + 1, y));
-		}
+	fn collect_neighbors_of(&self, dst: &mut Vec<Pt>, pt: &Pt) {
+		dst.extend(self.heights.neighbors(pt, self.neighborhood.offsets(pt.1)));
 	}
 
-	fn find_low_points(&self) -> Vec<(Pt, u8)> {
-		let (w, h) = self.size();
-		let mut neighbors: Vec<Pt> = Vec::with_capacity(4);
+	// Finds the points that are lower than all of their neighbors. Walls are
+	// never low points and aren't counted as neighbors, so a low point is the
+	// lowest point in its part of the basin.
+	fn find_low_points(&self) -> Vec<(Pt, u32)> {
+		let mut neighbors: Vec<Pt> = Vec::with_capacity(8);
 		let mut low_points = Vec::new();
-		for x in 0..w {
-			for y in 0..h {
-				neighbors.clear();
-				let pt = (x, y);
-				self.collect_neighbors_of(&mut neighbors, &pt);
-				let v = self.get(&pt);
-				if v < neighbors.iter().map(|pt| self.get(pt)).min().unwrap() {
-					low_points.push((pt, v));
-				}
+		for pt in self.heights.points() {
+			if self.is_wall(&pt) {
+				continue;
+			}
+			neighbors.clear();
+			self.collect_neighbors_of(&mut neighbors, &pt);
+			let v = self.get(&pt);
+			if neighbors
+				.iter()
+				.filter(|pt| !self.is_wall(pt))
+				.all(|pt| v < self.get(pt))
+			{
+				low_points.push((pt, v));
 			}
 		}
 		low_points
 	}
 
-	fn find_basin_at(&self, pt: &Pt) -> HashSet<Pt> {
-		flood::fill(pt, |x, y, g| {
+	// Finds the points in the basin containing pt that aren't already marked
+	// in filled, marking them as it goes.
+	fn find_basin_at(&self, pt: &Pt, filled: &mut Grid<bool>) -> Vec<Pt> {
+		if self.neighborhood != Neighborhood::Four {
+			return self.search_basin_at(pt, filled);
+		}
+		flood::fill(pt, filled, |x, y, g| {
 			let (w, h) = self.size();
 			let pt = (x as usize, y as usize);
-			x >= 0
-				&& y >= 0 && x < w as isize
-				&& y < h as isize
-				&& !g.contains(&pt)
-				&& self.get(&pt) < 9
+			x >= 0 && y >= 0 && x < w as isize && y < h as isize && !g[pt] && !self.is_wall(&pt)
 		})
 	}
+
+	// Finds the basin with a breadth first search, which unlike the span
+	// filling in flood works for any neighborhood.
+	fn search_basin_at(&self, pt: &Pt, filled: &mut Grid<bool>) -> Vec<Pt> {
+		let mut basin = Vec::new();
+		if self.is_wall(pt) || filled[*pt] {
+			return basin;
+		}
+		let mut queue = VecDeque::new();
+		let mut neighbors = Vec::with_capacity(8);
+		filled[*pt] = true;
+		queue.push_back(*pt);
+		while let Some(pt) = queue.pop_front() {
+			basin.push(pt);
+			neighbors.clear();
+			self.collect_neighbors_of(&mut neighbors, &pt);
+			for n in neighbors.iter() {
+				if !self.is_wall(n) && !filled[*n] {
+					filled[*n] = true;
+					queue.push_back(*n);
+				}
+			}
+		}
+		basin
+	}
 }
 
 // DrainSorted is here because BinaryHeap::drain_sorted is experimental
@@ -165,6 +251,123 @@ impl<'a, T: Ord> DrainSorted<'a, T> {
 	}
 }
 
+// Makes sure the basin flooded from each low point is exactly the points
+// labeled with its basin. Basins without a low point, like a flat stretch,
+// are flooded from their first point so every label is checked, and they are
+// counted since the flood fill alone never found them.
+fn check(
+	map: &Map,
+	low_points: &[(Pt, u32)],
+	labels: &label::Labels,
+) -> Result<(), Box<dyn Error>> {
+	let (w, h) = map.size();
+	let mut filled = Grid::filled(w, h, false);
+	let mut seen = vec![false; labels.sizes().len()];
+	let mut without_low_point = 0;
+	let seeds = low_points
+		.iter()
+		.map(|&(pt, _)| (pt, true))
+		.chain(map.heights.points().map(|pt| (pt, false)));
+	for (pt, is_low_point) in seeds {
+		let label = match labels.get(&pt) {
+			Some(l) => l,
+			None if is_low_point => {
+				return Err(format!("low point {:?} is not in a basin", pt).into())
+			}
+			None => continue,
+		};
+		if seen[label] {
+			continue;
+		}
+		let basin = map.find_basin_at(&pt, &mut filled);
+		if let Some(p) = basin.iter().find(|p| labels.get(p) != Some(label)) {
+			return Err(format!(
+				"{:?} is flooded from {:?} but labeled {:?} rather than {}",
+				p,
+				pt,
+				labels.get(p),
+				label
+			)
+			.into());
+		}
+		if basin.len() != labels.sizes()[label] {
+			return Err(format!(
+				"basin at {:?} has {} points but label {} has {}",
+				pt,
+				basin.len(),
+				label,
+				labels.sizes()[label]
+			)
+			.into());
+		}
+		seen[label] = true;
+		if !is_low_point {
+			without_low_point += 1;
+		}
+	}
+	if without_low_point > 0 {
+		println!("{} basins have no low point", without_low_point);
+	}
+	Ok(())
+}
+
+fn report_watershed(map: &Map, labels: &label::Labels) {
+	let ws = watershed::Watershed::new(map);
+	let mut basins = ws
+		.basins()
+		.into_iter()
+		.map(|(sink, size)| (size, sink))
+		.collect::<Vec<_>>();
+	basins.sort_unstable_by(|a, b| b.cmp(a));
+	println!("Watershed: {} sinks", basins.len());
+	for (size, sink) in basins.iter().take(3) {
+		println!("  {} points drain to {:?}", size, sink);
+	}
+	println!(
+		"  Largest three: {}",
+		basins
+			.iter()
+			.take(3)
+			.map(|(size, _)| size)
+			.product::<usize>()
+	);
+	if let Some((pt, flow)) = ws.max_flow() {
+		println!("  Most flow: {} points through {:?}", flow, pt);
+	}
+
+	let d = watershed::compare(map, &ws, labels);
+	println!("  {} wall points drain into a basin", d.into_basins.len());
+	println!(
+		"  {} points drain out of their walled basin",
+		d.out_of_basins.len()
+	);
+	for (pt, sink) in d.out_of_basins.iter().take(10) {
+		println!("    {:?} drains to {:?}", pt, sink);
+	}
+	if d.out_of_basins.len() > 10 {
+		println!("    ...");
+	}
+	println!(
+		"  {} walled basins drain to more than one sink",
+		d.split.len()
+	);
+	for (basin, sinks) in &d.split {
+		let shown = sinks.iter().take(5).collect::<Vec<_>>();
+		let more = if sinks.len() > shown.len() {
+			", ..."
+		} else {
+			""
+		};
+		println!(
+			"    basin {} drains to {} sinks: {:?}{}",
+			basin,
+			sinks.len(),
+			shown,
+			more
+		);
+	}
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
 	let matches = clap::App::new("day09")
 		.arg(
@@ -172,11 +375,64 @@ fn main() -> Result<(), Box<dyn Error>> {
 				.takes_value(true)
 				.help("the input file"),
 		)
+		.arg(
+			clap::Arg::with_name("neighborhood")
+				.long("neighborhood")
+				.takes_value(true)
+				.possible_values(&["4", "8", "hex"])
+				.default_value("4")
+				.help("which points are next to each other"),
+		)
+		.arg(
+			clap::Arg::with_name("wall")
+				.long("wall")
+				.takes_value(true)
+				.default_value(">=9")
+				.help("the heights of walls: a threshold like >=9 or a list like 7,9"),
+		)
+		.arg(
+			clap::Arg::with_name("wall-mask")
+				.long("wall-mask")
+				.takes_value(true)
+				.help("a file marking walls with # and everything else with ."),
+		)
+		.arg(
+			clap::Arg::with_name("basins")
+				.long("basins")
+				.help("show the basins in color in the terminal"),
+		)
+		.arg(
+			clap::Arg::with_name("pgm")
+				.long("pgm")
+				.takes_value(true)
+				.help("write the heights as a grayscale PGM image"),
+		)
+		.arg(
+			clap::Arg::with_name("ppm")
+				.long("ppm")
+				.takes_value(true)
+				.help("write the basins in color as a PPM image"),
+		)
+		.arg(
+			clap::Arg::with_name("watershed")
+				.long("watershed")
+				.help("find the basins water drains into and compare them to the walled basins"),
+		)
+		.arg(
+			clap::Arg::with_name("check")
+				.long("check")
+				.help("check the basins against a flood fill from each low point"),
+		)
 		.get_matches();
 
-	let map = Map::from_reader(File::open(
+	let mut map = Map::from_reader(File::open(
 		matches.value_of("input").unwrap_or("data/day09/input.txt"),
 	)?)?;
+	map.neighborhood = matches.value_of("neighborhood").unwrap().parse()?;
+	map.wall = match matches.value_of("wall-mask") {
+		Some(path) => Wall::read_mask(&std::fs::read_to_string(path)?, map.size())?,
+		None => matches.value_of("wall").unwrap().parse()?,
+	};
 
 	let low_points = map.find_low_points();
 	println!(
@@ -187,13 +443,75 @@ fn main() -> Result<(), Box<dyn Error>> {
 			.sum::<usize>()
 	);
 
-	let mut basins = low_points
-		.iter()
-		.map(|(pt, _)| map.find_basin_at(pt).len())
-		.collect::<BinaryHeap<_>>();
+	let labels = label::Labels::new(&map);
+	if matches.is_present("check") {
+		check(&map, &low_points, &labels)?;
+	}
+
+	let mut basins = labels.sizes().iter().copied().collect::<BinaryHeap<_>>();
 	println!(
 		"Part 2: {}",
 		DrainSorted::from(&mut basins).take(3).product::<usize>()
 	);
+
+	if matches.is_present("watershed") {
+		report_watershed(&map, &labels);
+	}
+	if matches.is_present("basins") {
+		export::write_ansi(
+			&map,
+			&labels,
+			&low_points,
+			&mut BufWriter::new(io::stdout().lock()),
+		)?;
+	}
+	if let Some(path) = matches.value_of("pgm") {
+		export::write_pgm(&map, &mut BufWriter::new(File::create(path)?))?;
+	}
+	if let Some(path) = matches.value_of("ppm") {
+		export::write_ppm(
+			&map,
+			&labels,
+			&low_points,
+			&mut BufWriter::new(File::create(path)?),
+		)?;
+	}
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn rows(s: &str) -> Vec<Vec<u32>> {
+		Map::parse_heights(s)
+			.unwrap()
+			.rows()
+			.map(|row| row.to_vec())
+			.collect()
+	}
+
+	#[test]
+	fn digits() {
+		assert_eq!(rows("219\n398\n"), vec![vec![2, 1, 9], vec![3, 9, 8]]);
+		// trailing whitespace isn't a separator
+		assert_eq!(rows("219  \n398\t\n"), vec![vec![2, 1, 9], vec![3, 9, 8]]);
+		// without any separators, multi-digit rows are still digits
+		assert_eq!(rows("10\n12"), vec![vec![1, 0], vec![1, 2]]);
+	}
+
+	#[test]
+	fn separated() {
+		assert_eq!(rows("10, 200\n3 4\n"), vec![vec![10, 200], vec![3, 4]]);
+		// one separator anywhere makes every row separated
+		assert_eq!(rows("10,\n12"), vec![vec![10], vec![12]]);
+		assert_eq!(rows("10\n12,"), vec![vec![10], vec![12]]);
+	}
+
+	#[test]
+	fn errors() {
+		for s in ["1,2\n34", "12\n345", "1,2\n3", "1x\n23", "1,-2\n3,4"] {
+			assert!(Map::parse_heights(s).is_err(), "{:?}", s);
+		}
+	}
+}
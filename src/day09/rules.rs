@@ -0,0 +1,103 @@
+use aoc::grid::{self, Grid, Pt};
+use std::error::Error;
+use std::str::FromStr;
+
+// Which points are next to each other on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+	// up, down, left and right
+	Four,
+	// including the diagonals
+	Eight,
+	// Hexagonal cells where every odd row is shifted right by half a cell, so
+	// each point touches two points in the rows above and below it.
+	Hex,
+}
+
+impl Neighborhood {
+	// the (dx, dy) offsets of the neighbors of a point in row y.
+	pub fn offsets(self, y: usize) -> &'static [(isize, isize)] {
+		match self {
+			Neighborhood::Four => grid::FOUR,
+			Neighborhood::Eight => grid::EIGHT,
+			Neighborhood::Hex if y % 2 == 0 => {
+				&[(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)]
+			}
+			Neighborhood::Hex => &[(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)],
+		}
+	}
+}
+
+impl FromStr for Neighborhood {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"4" => Ok(Neighborhood::Four),
+			"8" => Ok(Neighborhood::Eight),
+			"hex" => Ok(Neighborhood::Hex),
+			_ => Err(format!("invalid neighborhood: {}", s).into()),
+		}
+	}
+}
+
+// Which points are walls that separate the basins from each other.
+#[derive(Debug, Clone)]
+pub enum Wall {
+	// every point at least this high
+	AtLeast(u32),
+	// every point with one of these heights
+	Heights(Vec<u32>),
+	// the points marked with # in a mask the same size as the map
+	Mask(Grid<bool>),
+}
+
+impl Wall {
+	// whether the point at pt, with the given height, is a wall.
+	pub fn contains(&self, pt: &Pt, height: u32) -> bool {
+		match self {
+			Wall::AtLeast(h) => height >= *h,
+			Wall::Heights(hs) => hs.contains(&height),
+			Wall::Mask(mask) => mask[*pt],
+		}
+	}
+
+	// Reads a mask of # for walls and . for everything else, which must have
+	// the given width and height.
+	pub fn read_mask(s: &str, size: (usize, usize)) -> Result<Wall, Box<dyn Error>> {
+		let mask = Grid::parse_with(s, |c| match c {
+			'#' => Some(true),
+			'.' => Some(false),
+			_ => None,
+		})
+		.map_err(|e| format!("mask {}", e))?;
+		if mask.size() != size {
+			return Err(format!(
+				"mask is {}x{} but the map is {}x{}",
+				mask.width(),
+				mask.height(),
+				size.0,
+				size.1
+			)
+			.into());
+		}
+		Ok(Wall::Mask(mask))
+	}
+}
+
+// Parses either a threshold like `>=9` or a list of heights like `7,9`.
+impl FromStr for Wall {
+	type Err = Box<dyn Error>;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.strip_prefix(">=") {
+			Some(h) => Ok(Wall::AtLeast(h.trim().parse()?)),
+			None => Ok(Wall::Heights(
+				s.split(',')
+					.map(|h| h.trim().parse::<u32>())
+					.collect::<Result<Vec<_>, _>>()
+					.map_err(|_| format!("invalid wall: {}", s))?,
+			)),
+		}
+	}
+}
@@ -0,0 +1,161 @@
+use super::label::Labels;
+use super::Map;
+use aoc::grid::{Grid, Pt};
+
+// Where water falling on each point of the map ends up when it always runs
+// to the lowest neighbor. Unlike the basins bounded by walls, every point
+// drains somewhere, walls included.
+#[derive(Debug)]
+pub struct Watershed {
+	// the sink each point eventually drains to
+	sinks: Grid<Pt>,
+	// the number of points whose water flows through each point, itself
+	// included
+	flow: Grid<usize>,
+}
+
+impl Watershed {
+	// Points drain to their lowest neighbor as long as it's lower than they
+	// are, with ties going to the first neighbor found. A point with no lower
+	// neighbor is a sink, so every point on a flat stretch is its own sink.
+	pub fn new(map: &Map) -> Watershed {
+		let (w, h) = map.size();
+		let mut neighbors = Vec::with_capacity(8);
+		// the neighbor each point drains to, or None for a sink
+		let mut downhill = Grid::filled(w, h, None);
+		for pt in map.heights.points() {
+			neighbors.clear();
+			map.collect_neighbors_of(&mut neighbors, &pt);
+			downhill[pt] = neighbors
+				.iter()
+				.copied()
+				.filter(|n| map.get(n) < map.get(&pt))
+				.min_by_key(|n| map.get(n));
+		}
+
+		// follow the water from each point until it reaches a sink or a
+		// point whose sink is already known, then fill in the whole path
+		const UNKNOWN: Pt = (usize::MAX, usize::MAX);
+		let mut sinks = Grid::filled(w, h, UNKNOWN);
+		let mut path = Vec::new();
+		for pt in map.heights.points() {
+			let mut p = pt;
+			let sink = loop {
+				if sinks[p] != UNKNOWN {
+					break sinks[p];
+				}
+				path.push(p);
+				match downhill[p] {
+					Some(d) => p = d,
+					None => break p,
+				}
+			};
+			for p in path.drain(..) {
+				sinks[p] = sink;
+			}
+		}
+
+		// pass the flow down from the points nothing drains into, only moving
+		// on from a point once everything that drains into it has been seen
+		let mut uphill = Grid::filled(w, h, 0_u8);
+		for (_, d) in downhill.iter() {
+			if let Some(d) = d {
+				uphill[*d] += 1;
+			}
+		}
+		let mut flow = Grid::filled(w, h, 1);
+		let mut ready = uphill
+			.iter()
+			.filter(|&(_, &n)| n == 0)
+			.map(|(pt, _)| pt)
+			.collect::<Vec<_>>();
+		while let Some(pt) = ready.pop() {
+			if let Some(d) = downhill[pt] {
+				flow[d] += flow[pt];
+				uphill[d] -= 1;
+				if uphill[d] == 0 {
+					ready.push(d);
+				}
+			}
+		}
+
+		Watershed { sinks, flow }
+	}
+
+	pub fn sink(&self, pt: &Pt) -> Pt {
+		self.sinks[*pt]
+	}
+
+	// the number of points that drain to each sink, in the order of the sinks.
+	pub fn basins(&self) -> Vec<(Pt, usize)> {
+		let (w, h) = self.sinks.size();
+		let mut sizes = Grid::filled(w, h, 0);
+		for (_, &sink) in self.sinks.iter() {
+			sizes[sink] += 1;
+		}
+		sizes
+			.iter()
+			.filter(|&(_, &n)| n > 0)
+			.map(|(pt, &n)| (pt, n))
+			.collect()
+	}
+
+	// the point with the most water flowing through it.
+	pub fn max_flow(&self) -> Option<(Pt, usize)> {
+		self.flow
+			.iter()
+			.map(|(pt, &f)| (pt, f))
+			.max_by_key(|&(pt, f)| (f, std::cmp::Reverse(pt)))
+	}
+}
+
+// The places where draining to a sink and being bounded by walls don't give
+// the same basins.
+#[derive(Debug, Default)]
+pub struct Disagreements {
+	// points outside of any walled basin whose water ends up in one, as
+	// (point, sink)
+	pub into_basins: Vec<(Pt, Pt)>,
+	// points in a walled basin that drain to a sink outside of it
+	pub out_of_basins: Vec<(Pt, Pt)>,
+	// walled basins whose points drain to more than one sink, with the sinks
+	pub split: Vec<(usize, Vec<Pt>)>,
+}
+
+pub fn compare(map: &Map, watershed: &Watershed, labels: &Labels) -> Disagreements {
+	let mut d = Disagreements::default();
+	// the first sink found for each basin, and any other (basin, sink)
+	// pairs, which are sorted so each basin's sinks are together
+	let mut first = vec![None; labels.sizes().len()];
+	let mut others = Vec::new();
+	for pt in map.heights.points() {
+		let sink = watershed.sink(&pt);
+		let basin = match (labels.get(&pt), labels.get(&sink)) {
+			(Some(a), Some(b)) if a == b => a,
+			(Some(a), _) => {
+				d.out_of_basins.push((pt, sink));
+				a
+			}
+			(None, Some(_)) => {
+				d.into_basins.push((pt, sink));
+				continue;
+			}
+			(None, None) => continue,
+		};
+		match first[basin] {
+			None => first[basin] = Some(sink),
+			Some(s) if s != sink => others.push((basin, sink)),
+			_ => {}
+		}
+	}
+	others.sort_unstable();
+	others.dedup();
+	for run in others.chunk_by(|a, b| a.0 == b.0) {
+		let basin = run[0].0;
+		let mut sinks = run.iter().map(|&(_, sink)| sink).collect::<Vec<_>>();
+		sinks.extend(first[basin]);
+		sinks.sort_unstable();
+		d.split.push((basin, sinks));
+	}
+	d
+}
@@ -0,0 +1,388 @@
+use std::error::Error;
+use std::ops::{Index, IndexMut};
+
+// A point on a grid as (x, y), where y counts rows down from the top.
+pub type Pt = (usize, usize);
+
+// The offsets of the points up, down, left and right of a point.
+pub const FOUR: &[(isize, isize)] = &[(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+// The offsets of the points around a point, including the diagonals.
+pub const EIGHT: &[(isize, isize)] = &[
+	(-1, -1),
+	(0, -1),
+	(1, -1),
+	(-1, 0),
+	(1, 0),
+	(-1, 1),
+	(0, 1),
+	(1, 1),
+];
+
+// A rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+	width: usize,
+	height: usize,
+	cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+	// Makes a grid from cells given row by row, which must fill every row.
+	pub fn new(width: usize, cells: Vec<T>) -> Result<Grid<T>, Box<dyn Error>> {
+		if width == 0 {
+			if !cells.is_empty() {
+				return Err("a grid with cells must have a width".into());
+			}
+			return Ok(Grid {
+				width,
+				height: 0,
+				cells,
+			});
+		}
+		if cells.len() % width != 0 {
+			return Err(format!("{} cells do not fill rows of {}", cells.len(), width).into());
+		}
+		Ok(Grid {
+			width,
+			height: cells.len() / width,
+			cells,
+		})
+	}
+
+	pub fn filled(width: usize, height: usize, v: T) -> Grid<T>
+	where
+		T: Clone,
+	{
+		Grid {
+			width,
+			height,
+			cells: vec![v; width * height],
+		}
+	}
+
+	// Parses a grid with a cell for each character, like the puzzle maps.
+	// Every line must have the same number of characters and f turns each
+	// character into a cell, or returns None if it isn't valid.
+	pub fn parse_with<F>(s: &str, f: F) -> Result<Grid<T>, Box<dyn Error>>
+	where
+		F: Fn(char) -> Option<T>,
+	{
+		let mut width = None;
+		let mut cells = Vec::new();
+		for (i, line) in s.lines().enumerate() {
+			let n = line.chars().count();
+			match width {
+				None => width = Some(n),
+				Some(w) if w != n => {
+					return Err(format!("line {}: expected {} cells, found {}", i + 1, w, n).into())
+				}
+				_ => {}
+			}
+			for (j, c) in line.chars().enumerate() {
+				match f(c) {
+					Some(v) => cells.push(v),
+					None => {
+						return Err(format!(
+							"line {}, column {}: invalid cell: {}",
+							i + 1,
+							j + 1,
+							c
+						)
+						.into())
+					}
+				}
+			}
+		}
+		Grid::new(width.unwrap_or(0), cells)
+	}
+
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	// the (width, height) of the grid.
+	pub fn size(&self) -> (usize, usize) {
+		(self.width, self.height)
+	}
+
+	pub fn contains(&self, pt: &Pt) -> bool {
+		let (x, y) = *pt;
+		x < self.width && y < self.height
+	}
+
+	fn index_of(&self, pt: &Pt) -> Option<usize> {
+		let (x, y) = *pt;
+		if self.contains(pt) {
+			Some(y * self.width + x)
+		} else {
+			None
+		}
+	}
+
+	pub fn get(&self, pt: &Pt) -> Option<&T> {
+		self.index_of(pt).map(|ix| &self.cells[ix])
+	}
+
+	pub fn get_mut(&mut self, pt: &Pt) -> Option<&mut T> {
+		self.index_of(pt).map(move |ix| &mut self.cells[ix])
+	}
+
+	// Replaces the cell at pt, returning what was there before or None if
+	// pt is off the grid.
+	pub fn set(&mut self, pt: &Pt, v: T) -> Option<T> {
+		self.get_mut(pt).map(|c| std::mem::replace(c, v))
+	}
+
+	pub fn row(&self, y: usize) -> &[T] {
+		&self.cells[y * self.width..(y + 1) * self.width]
+	}
+
+	pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+		(0..self.height).map(move |y| self.row(y))
+	}
+
+	pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+		assert!(x < self.width, "column {} is off the grid", x);
+		self.cells.iter().skip(x).step_by(self.width)
+	}
+
+	pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+		(0..self.width).map(move |x| self.column(x))
+	}
+
+	// every point on the grid in row order.
+	pub fn points(&self) -> impl Iterator<Item = Pt> {
+		let (w, h) = self.size();
+		(0..h).flat_map(move |y| (0..w).map(move |x| (x, y)))
+	}
+
+	// every point along with its cell in row order.
+	pub fn iter(&self) -> impl Iterator<Item = (Pt, &T)> {
+		self.points().zip(self.cells.iter())
+	}
+
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (Pt, &mut T)> {
+		let (w, h) = self.size();
+		(0..h)
+			.flat_map(move |y| (0..w).map(move |x| (x, y)))
+			.zip(self.cells.iter_mut())
+	}
+
+	// The points at the given offsets from pt that are on the grid. FOUR and
+	// EIGHT are the usual offsets.
+	pub fn neighbors<'a>(
+		&self,
+		pt: &Pt,
+		offsets: &'a [(isize, isize)],
+	) -> impl Iterator<Item = Pt> + 'a {
+		let (w, h) = (self.width as isize, self.height as isize);
+		let (x, y) = (pt.0 as isize, pt.1 as isize);
+		offsets.iter().filter_map(move |&(dx, dy)| {
+			let (nx, ny) = (x + dx, y + dy);
+			if nx >= 0 && ny >= 0 && nx < w && ny < h {
+				Some((nx as usize, ny as usize))
+			} else {
+				None
+			}
+		})
+	}
+
+	pub fn map<U, F>(&self, f: F) -> Grid<U>
+	where
+		F: FnMut(&T) -> U,
+	{
+		Grid {
+			width: self.width,
+			height: self.height,
+			cells: self.cells.iter().map(f).collect(),
+		}
+	}
+
+	// Builds a grid of the given size by asking f for the cell at each point.
+	fn build<F>(width: usize, height: usize, f: F) -> Grid<T>
+	where
+		F: FnMut(Pt) -> T,
+	{
+		Grid {
+			width,
+			height,
+			cells: (0..height)
+				.flat_map(|y| (0..width).map(move |x| (x, y)))
+				.map(f)
+				.collect(),
+		}
+	}
+
+	// flips the grid over its main diagonal, so rows become columns.
+	pub fn transpose(&self) -> Grid<T>
+	where
+		T: Clone,
+	{
+		Grid::build(self.height, self.width, |(x, y)| self[(y, x)].clone())
+	}
+
+	// rotates the grid a quarter turn clockwise.
+	pub fn rotate_cw(&self) -> Grid<T>
+	where
+		T: Clone,
+	{
+		let h = self.height;
+		Grid::build(self.height, self.width, |(x, y)| {
+			self[(y, h - 1 - x)].clone()
+		})
+	}
+
+	// rotates the grid a quarter turn counterclockwise.
+	pub fn rotate_ccw(&self) -> Grid<T>
+	where
+		T: Clone,
+	{
+		let w = self.width;
+		Grid::build(self.height, self.width, |(x, y)| {
+			self[(w - 1 - y, x)].clone()
+		})
+	}
+}
+
+impl Grid<u8> {
+	// parses a map with a single digit in each cell.
+	pub fn parse_digits(s: &str) -> Result<Grid<u8>, Box<dyn Error>> {
+		Grid::parse_with(s, |c| c.to_digit(10).map(|d| d as u8))
+	}
+}
+
+impl Grid<char> {
+	pub fn parse_chars(s: &str) -> Result<Grid<char>, Box<dyn Error>> {
+		Grid::parse_with(s, Some)
+	}
+}
+
+impl<T> Index<Pt> for Grid<T> {
+	type Output = T;
+
+	fn index(&self, pt: Pt) -> &T {
+		match self.index_of(&pt) {
+			Some(ix) => &self.cells[ix],
+			None => panic!("{:?} is off the {}x{} grid", pt, self.width, self.height),
+		}
+	}
+}
+
+impl<T> IndexMut<Pt> for Grid<T> {
+	fn index_mut(&mut self, pt: Pt) -> &mut T {
+		match self.index_of(&pt) {
+			Some(ix) => &mut self.cells[ix],
+			None => panic!("{:?} is off the {}x{} grid", pt, self.width, self.height),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// a 3 wide, 2 high grid:
+	//   123
+	//   456
+	fn grid() -> Grid<u8> {
+		Grid::parse_digits("123\n456\n").unwrap()
+	}
+
+	fn rows<T: Clone>(g: &Grid<T>) -> Vec<Vec<T>> {
+		g.rows().map(|row| row.to_vec()).collect()
+	}
+
+	#[test]
+	fn new_checks_the_rows_are_full() {
+		let g = Grid::new(3, vec![1, 2, 3, 4, 5, 6]).unwrap();
+		assert_eq!(g.size(), (3, 2));
+		assert!(Grid::new(4, vec![1, 2, 3, 4, 5, 6]).is_err());
+	}
+
+	#[test]
+	fn new_with_zero_width() {
+		let g = Grid::<u8>::new(0, Vec::new()).unwrap();
+		assert_eq!(g.size(), (0, 0));
+		assert_eq!(g.points().count(), 0);
+		assert_eq!(g.rows().count(), 0);
+		assert!(!g.contains(&(0, 0)));
+		assert!(Grid::new(0, vec![1]).is_err());
+	}
+
+	#[test]
+	fn parse_with() {
+		assert_eq!(rows(&grid()), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+		assert_eq!(Grid::parse_chars("").unwrap().size(), (0, 0));
+	}
+
+	#[test]
+	fn parse_with_ragged_rows() {
+		let err = Grid::parse_digits("123\n45\n").unwrap_err();
+		assert_eq!(err.to_string(), "line 2: expected 3 cells, found 2");
+	}
+
+	#[test]
+	fn parse_with_invalid_cells() {
+		let err = Grid::parse_digits("123\n4x6\n").unwrap_err();
+		assert_eq!(err.to_string(), "line 2, column 2: invalid cell: x");
+	}
+
+	#[test]
+	fn get_and_set() {
+		let mut g = grid();
+		assert_eq!(g.get(&(2, 1)), Some(&6));
+		assert_eq!(g.get(&(3, 0)), None);
+		assert_eq!(g.set(&(0, 1), 9), Some(4));
+		assert_eq!(g[(0, 1)], 9);
+		assert_eq!(g.set(&(0, 2), 9), None);
+	}
+
+	#[test]
+	fn columns() {
+		let g = grid();
+		let columns = g
+			.columns()
+			.map(|c| c.copied().collect::<Vec<_>>())
+			.collect::<Vec<_>>();
+		assert_eq!(columns, vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+	}
+
+	#[test]
+	fn transpose() {
+		let t = grid().transpose();
+		assert_eq!(t.size(), (2, 3));
+		assert_eq!(rows(&t), vec![vec![1, 4], vec![2, 5], vec![3, 6]]);
+		assert_eq!(t.transpose(), grid());
+	}
+
+	#[test]
+	fn rotate_cw() {
+		let r = grid().rotate_cw();
+		assert_eq!(r.size(), (2, 3));
+		assert_eq!(rows(&r), vec![vec![4, 1], vec![5, 2], vec![6, 3]]);
+		assert_eq!(r.rotate_cw().rotate_cw().rotate_cw(), grid());
+	}
+
+	#[test]
+	fn rotate_ccw() {
+		let r = grid().rotate_ccw();
+		assert_eq!(r.size(), (2, 3));
+		assert_eq!(rows(&r), vec![vec![3, 6], vec![2, 5], vec![1, 4]]);
+		assert_eq!(r.rotate_cw(), grid());
+	}
+
+	#[test]
+	fn neighbors() {
+		let g = grid();
+		let around = |pt: Pt, offsets| g.neighbors(&pt, offsets).collect::<Vec<_>>();
+		assert_eq!(around((1, 0), FOUR), vec![(1, 1), (0, 0), (2, 0)]);
+		assert_eq!(around((0, 0), FOUR), vec![(0, 1), (1, 0)]);
+		assert_eq!(around((2, 1), EIGHT), vec![(1, 0), (2, 0), (1, 1)]);
+		assert_eq!(around((1, 1), EIGHT).len(), 5);
+	}
+}
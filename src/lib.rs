@@ -0,0 +1,6 @@
+// Code shared by more than one day.
+
+pub mod grid;
+pub mod parts;
+pub mod rng;
+pub mod segments;
@@ -0,0 +1,21 @@
+use std::panic;
+use std::thread;
+
+// Runs the two parts of a day at the same time, each on its own thread, and
+// waits for both. Days whose parts only share the parsed input take as long
+// as their slower part this way. The answers come back in order no matter
+// which part finishes first, and a panic in either part is passed on.
+pub fn both<A, B, FA, FB>(part1: FA, part2: FB) -> (A, B)
+where
+	A: Send,
+	B: Send,
+	FA: FnOnce() -> A + Send,
+	FB: FnOnce() -> B + Send,
+{
+	thread::scope(|s| {
+		let part2 = s.spawn(part2);
+		let a = part1();
+		let b = part2.join().unwrap_or_else(|e| panic::resume_unwind(e));
+		(a, b)
+	})
+}
@@ -0,0 +1,35 @@
+// A small seeded pseudo random number generator (SplitMix64) so that the same
+// seed always generates the same inputs.
+// See https://prng.di.unimi.it/splitmix64.c
+pub struct Rng(u64);
+
+impl Rng {
+	pub fn new(seed: u64) -> Rng {
+		Rng(seed)
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+		z ^ (z >> 31)
+	}
+
+	// a number in 0..n
+	pub fn below(&mut self, n: usize) -> usize {
+		(self.next_u64() % n as u64) as usize
+	}
+
+	// a number in lo..=hi
+	pub fn between(&mut self, lo: isize, hi: isize) -> isize {
+		lo + self.below((hi - lo + 1) as usize) as isize
+	}
+
+	// a Fisher-Yates shuffle.
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			items.swap(i, self.below(i + 1));
+		}
+	}
+}
@@ -0,0 +1,139 @@
+// Randomly wired seven-segment displays like the ones in day08, which the
+// input generator builds as well.
+use crate::rng::Rng;
+use std::error::Error;
+
+// the segments lit for the digits 0 through 9 on a seven-segment display.
+pub const DIGITS: &str = "
+0 abcefg
+1 cf
+2 acdeg
+3 acdfg
+4 bcdf
+5 abdfg
+6 abdefg
+7 acf
+8 abcdefg
+9 abcdfg
+";
+
+// the letters for the segments a pattern lights, in order.
+fn letters(signals: u32) -> String {
+	(0..26)
+		.filter(|i| signals & (1 << i) != 0)
+		.map(|i| (b'a' + i as u8) as char)
+		.collect()
+}
+
+// Parses a table with a symbol and the segments it lights on each line, like
+// `7 acf`, into each symbol with a bit for every segment. Blank lines and
+// lines starting with # are ignored. Every symbol has to light a different
+// set of segments.
+pub fn parse_glyphs(s: &str) -> Result<Vec<(char, u32)>, Box<dyn Error>> {
+	let mut glyphs: Vec<(char, u32)> = Vec::new();
+	for (i, line) in s.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		let (symbol, segments) = match line.split_once(char::is_whitespace) {
+			Some((symbol, segments)) => (symbol, segments.trim()),
+			None => return Err(format!("line {}: invalid glyph: {}", i + 1, line).into()),
+		};
+		let mut chars = symbol.chars();
+		let symbol = match (chars.next(), chars.next()) {
+			(Some(c), None) => c,
+			_ => return Err(format!("line {}: invalid symbol: {}", i + 1, symbol).into()),
+		};
+		let mut signals = 0;
+		for c in segments.chars() {
+			match c {
+				'a'..='z' => signals |= 1 << (c as u32 - 'a' as u32),
+				_ => return Err(format!("line {}: invalid signal: {}", i + 1, c).into()),
+			}
+		}
+
+		if let Some(&(_, other)) = glyphs.iter().find(|&&(s, _)| s == symbol) {
+			return Err(format!(
+				"line {}: {} is already defined as {}",
+				i + 1,
+				symbol,
+				letters(other)
+			)
+			.into());
+		}
+		if let Some(&(other, _)) = glyphs.iter().find(|&&(_, p)| p == signals) {
+			return Err(format!(
+				"line {}: {} and {} are both {}",
+				i + 1,
+				other,
+				symbol,
+				letters(signals)
+			)
+			.into());
+		}
+		glyphs.push((symbol, signals));
+	}
+
+	if glyphs.is_empty() {
+		return Err("no glyphs".into());
+	}
+	Ok(glyphs)
+}
+
+// A randomly wired display along with the symbols it was asked to show.
+pub struct Generated {
+	pub line: String,
+	pub symbols: String,
+}
+
+// writes the wires of a pattern in a random order, like the puzzle input.
+fn scrambled_letters(signals: u32, rng: &mut Rng) -> String {
+	let mut letters = letters(signals).into_bytes();
+	rng.shuffle(&mut letters);
+	String::from_utf8(letters).unwrap()
+}
+
+// Picks a random wiring for a display with the given number of segments,
+// then emits every glyph in a random order followed by four random glyphs,
+// all in the format day08 parses.
+pub fn generate(glyphs: &[(char, u32)], segments: usize, rng: &mut Rng) -> Generated {
+	let mut wires = (0..segments).collect::<Vec<_>>();
+	rng.shuffle(&mut wires);
+
+	// the wires that light a glyph's segments
+	let scramble = |signals: u32| {
+		let mut wired = 0;
+		for (segment, &wire) in wires.iter().enumerate() {
+			if signals & (1 << segment) != 0 {
+				wired |= 1 << wire;
+			}
+		}
+		wired
+	};
+
+	let mut patterns = glyphs
+		.iter()
+		.map(|&(_, signals)| scramble(signals))
+		.collect::<Vec<_>>();
+	rng.shuffle(&mut patterns);
+
+	let output = (0..4)
+		.map(|_| glyphs[rng.below(glyphs.len())])
+		.collect::<Vec<_>>();
+
+	let patterns = patterns
+		.into_iter()
+		.map(|p| scrambled_letters(p, rng))
+		.collect::<Vec<_>>();
+	let scrambled_output = output
+		.iter()
+		.map(|&(_, signals)| scrambled_letters(scramble(signals), rng))
+		.collect::<Vec<_>>();
+
+	Generated {
+		line: format!("{} | {}", patterns.join(" "), scrambled_output.join(" ")),
+		symbols: output.iter().map(|&(symbol, _)| symbol).collect(),
+	}
+}
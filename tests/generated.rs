@@ -0,0 +1,60 @@
+use std::process::Command;
+
+// the binary for a day, which cargo builds for integration tests
+fn exe_of(day: usize) -> &'static str {
+	match day {
+		1 => env!("CARGO_BIN_EXE_day01"),
+		2 => env!("CARGO_BIN_EXE_day02"),
+		3 => env!("CARGO_BIN_EXE_day03"),
+		4 => env!("CARGO_BIN_EXE_day04"),
+		5 => env!("CARGO_BIN_EXE_day05"),
+		6 => env!("CARGO_BIN_EXE_day06"),
+		7 => env!("CARGO_BIN_EXE_day07"),
+		8 => env!("CARGO_BIN_EXE_day08"),
+		9 => env!("CARGO_BIN_EXE_day09"),
+		_ => unreachable!(),
+	}
+}
+
+#[test]
+fn days_solve_generated_inputs() {
+	let dir = env!("CARGO_TARGET_TMPDIR");
+	for day in 1..=9 {
+		for seed in 0..3 {
+			let file = format!("{}/day{:02}-{}.txt", dir, day, seed);
+			let status = Command::new(env!("CARGO_BIN_EXE_aoc"))
+				.args([
+					"generate",
+					&day.to_string(),
+					"--seed",
+					&seed.to_string(),
+					"-o",
+					&file,
+				])
+				.status()
+				.unwrap();
+			assert!(
+				status.success(),
+				"day {} seed {}: generate failed",
+				day,
+				seed
+			);
+
+			let out = Command::new(exe_of(day)).arg(&file).output().unwrap();
+			let stdout = String::from_utf8_lossy(&out.stdout);
+			assert!(
+				out.status.success(),
+				"day {} seed {}: {}",
+				day,
+				seed,
+				String::from_utf8_lossy(&out.stderr)
+			);
+			assert!(
+				!stdout.trim().is_empty(),
+				"day {} seed {}: no answers",
+				day,
+				seed
+			);
+		}
+	}
+}